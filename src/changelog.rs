@@ -0,0 +1,157 @@
+/// A parsed `CHANGELOG.md` file.
+///
+/// Two styles of section headers are recognized: Setext-style headers (a line
+/// underlined with `-` or `=` characters, e.g., "v1.2.3 (2025-01-01)"
+/// followed by a row of hyphens) and level-2 ATX-style headers (e.g.,
+/// "## [1.2.3] - 2025-01-01").  The version of each section is
+/// taken from the first word of the header, with any surrounding brackets and
+/// leading "v" removed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Changelog {
+    pub(crate) sections: Vec<ChangelogSection>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct ChangelogSection {
+    /// The full text of the section header
+    pub(crate) header: String,
+
+    /// The version the section is for
+    pub(crate) version: String,
+
+    /// The contents of the section, with leading & trailing blank lines
+    /// removed
+    pub(crate) body: String,
+}
+
+impl ChangelogSection {
+    /// Returns true if the section describes a version that has not been
+    /// released yet, i.e., if its header contains "in development" or
+    /// "unreleased"
+    pub(crate) fn in_development(&self) -> bool {
+        let header = self.header.to_ascii_lowercase();
+        header.contains("in development") || header.contains("unreleased")
+    }
+}
+
+impl Changelog {
+    pub(crate) fn parse(s: &str) -> Changelog {
+        let lines = s.lines().collect::<Vec<_>>();
+        let mut sections = Vec::new();
+        let mut current: Option<(String, Vec<&str>)> = None;
+        let mut i = 0;
+        while i < lines.len() {
+            let header = if let Some(h) = lines[i].strip_prefix("## ") {
+                i += 1;
+                Some(h.trim())
+            } else if lines
+                .get(i + 1)
+                .is_some_and(|ln| is_underline(ln) && !lines[i].trim().is_empty())
+            {
+                i += 2;
+                Some(lines[i - 2].trim())
+            } else {
+                None
+            };
+            if let Some(h) = header {
+                sections.extend(current.take().map(|(h, body)| make_section(h, &body)));
+                current = Some((h.to_owned(), Vec::new()));
+            } else {
+                if let Some((_, ref mut body)) = current {
+                    body.push(lines[i]);
+                }
+                i += 1;
+            }
+        }
+        sections.extend(current.map(|(h, body)| make_section(h, &body)));
+        Changelog { sections }
+    }
+
+    /// Return the section for the given version, if any.  Versions are
+    /// compared with any leading "v" removed.
+    pub(crate) fn section(&self, version: &str) -> Option<&ChangelogSection> {
+        let version = version.strip_prefix('v').unwrap_or(version);
+        self.sections.iter().find(|sect| sect.version == version)
+    }
+
+    /// Return the first section, which is conventionally the most recent
+    pub(crate) fn latest(&self) -> Option<&ChangelogSection> {
+        self.sections.first()
+    }
+}
+
+fn is_underline(line: &str) -> bool {
+    let line = line.trim_end();
+    !line.is_empty() && (line.chars().all(|c| c == '-') || line.chars().all(|c| c == '='))
+}
+
+fn make_section(header: String, body: &[&str]) -> ChangelogSection {
+    let word = header.split_whitespace().next().unwrap_or_default();
+    let word = word.trim_start_matches('[').trim_end_matches(']');
+    let version = word.strip_prefix('v').unwrap_or(word).to_owned();
+    let start = body
+        .iter()
+        .position(|ln| !ln.trim().is_empty())
+        .unwrap_or(body.len());
+    let end = body
+        .iter()
+        .rposition(|ln| !ln.trim().is_empty())
+        .map_or(start, |i| i + 1);
+    ChangelogSection {
+        header,
+        version,
+        body: body[start..end].join("\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_setext() {
+        let changelog = Changelog::parse(concat!(
+            "v0.2.0 (in development)\n",
+            "-----------------------\n",
+            "- Added a feature\n",
+            "\n",
+            "v0.1.1 (2025-02-01)\n",
+            "-------------------\n",
+            "- Fixed a bug\n",
+            "\n",
+            "- Fixed another bug\n",
+            "\n",
+            "v0.1.0 (2025-01-01)\n",
+            "-------------------\n",
+            "Initial release\n",
+        ));
+        assert_eq!(changelog.sections.len(), 3);
+        let latest = changelog.latest().unwrap();
+        assert_eq!(latest.version, "0.2.0");
+        assert!(latest.in_development());
+        let sect = changelog.section("v0.1.1").unwrap();
+        assert_eq!(sect.header, "v0.1.1 (2025-02-01)");
+        assert_eq!(sect.body, "- Fixed a bug\n\n- Fixed another bug");
+        assert!(!sect.in_development());
+        assert_eq!(changelog.section("0.1.0").unwrap().body, "Initial release");
+        assert_eq!(changelog.section("0.0.1"), None);
+    }
+
+    #[test]
+    fn test_parse_atx() {
+        let changelog = Changelog::parse(concat!(
+            "# Changelog\n",
+            "\n",
+            "## [Unreleased]\n",
+            "\n",
+            "## [1.0.0] - 2025-03-01\n",
+            "### Added\n",
+            "- Everything\n",
+        ));
+        assert_eq!(changelog.sections.len(), 2);
+        assert!(changelog.sections[0].in_development());
+        let sect = changelog.section("1.0.0").unwrap();
+        assert_eq!(sect.header, "[1.0.0] - 2025-03-01");
+        assert_eq!(sect.body, "### Added\n- Everything");
+    }
+}
@@ -2,9 +2,16 @@ use crate::logging::{Verbosity, is_active, logcmd};
 use bstr::ByteVec; // into_string_lossy()
 use std::ffi::OsStr;
 use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
 use thiserror::Error;
+use time::{OffsetDateTime, UtcOffset, format_description::FormatItem, macros::format_description};
+
+static LOG_TIMESTAMP_FORMAT: &[FormatItem<'_>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub(crate) enum CommandKind {
@@ -119,6 +126,111 @@ impl CommandPlus {
         }
     }
 
+    /// Run the command, always capturing its stdout and stderr.  If the
+    /// command's output would normally be shown, the captured output is
+    /// echoed after the command completes.
+    pub(crate) fn run_capture(&mut self) -> Result<CapturedOutput, CommandError> {
+        logcmd(self, self.kind.cmdline_verbosity());
+        let output = self.cmd.output().map_err(|source| CommandError::Startup {
+            cmdline: self.cmdline().clone(),
+            source,
+        })?;
+        let captured = CapturedOutput {
+            stdout: output.stdout.into_string_lossy(),
+            stderr: output.stderr.into_string_lossy(),
+        };
+        if output.status.success() {
+            if is_active(self.kind.output_verbosity()) {
+                print!("{}", captured.stdout);
+                eprint!("{}", captured.stderr);
+            }
+            Ok(captured)
+        } else {
+            Err(CommandError::Exit {
+                cmdline: self.cmdline().clone(),
+                rc: output.status,
+                stdout: Some(captured.stdout),
+                stderr: Some(captured.stderr),
+            })
+        }
+    }
+
+    /// Run the command, writing each line of its stdout and stderr to `log`
+    /// prefixed with a timestamp and the name of the stream.  If the
+    /// command's output would normally be shown, it is also passed through as
+    /// it is produced.
+    pub(crate) fn run_logged<W: Write>(&mut self, log: &mut W) -> Result<(), CommandError> {
+        logcmd(self, self.kind.cmdline_verbosity());
+        let echo = is_active(self.kind.output_verbosity());
+        // Determining the local offset can fail once there are multiple
+        // threads, so do it up front
+        let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        let timestamp = || {
+            OffsetDateTime::now_utc()
+                .to_offset(offset)
+                .format(&LOG_TIMESTAMP_FORMAT)
+                .expect("formatting a datetime should not fail")
+        };
+        let log_error = |source| CommandError::Log {
+            cmdline: self.cmdline.clone(),
+            source,
+        };
+        writeln!(log, "[{}] $ {}", timestamp(), self.cmdline).map_err(log_error)?;
+        let mut child = self
+            .cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|source| CommandError::Startup {
+                cmdline: self.cmdline.clone(),
+                source,
+            })?;
+        let stdout = child.stdout.take().expect("child stdout should be piped");
+        let stderr = child.stderr.take().expect("child stderr should be piped");
+        let (rc, logged) = thread::scope(|s| {
+            let (sender, receiver) = mpsc::channel();
+            let out_sender = sender.clone();
+            s.spawn(move || forward_lines(stdout, Stream::Stdout, &out_sender));
+            s.spawn(move || forward_lines(stderr, Stream::Stderr, &sender));
+            let mut logged = Ok(());
+            for (stream, line) in receiver {
+                if echo {
+                    stream.echo(&line);
+                }
+                if logged.is_ok() {
+                    logged = write!(log, "[{}] {stream}: ", timestamp())
+                        .and_then(|()| log.write_all(&line))
+                        .and_then(|()| {
+                            if line.ends_with(b"\n") {
+                                Ok(())
+                            } else {
+                                writeln!(log)
+                            }
+                        });
+                }
+            }
+            (child.wait(), logged)
+        });
+        let rc = rc.map_err(|source| CommandError::Startup {
+            cmdline: self.cmdline.clone(),
+            source,
+        })?;
+        logged
+            .and_then(|()| writeln!(log, "[{}] {rc}", timestamp()))
+            .and_then(|()| log.flush())
+            .map_err(log_error)?;
+        if rc.success() {
+            Ok(())
+        } else {
+            Err(CommandError::Exit {
+                cmdline: self.cmdline.clone(),
+                rc,
+                stdout: None,
+                stderr: None,
+            })
+        }
+    }
+
     pub(crate) fn status(&mut self) -> Result<ExitStatus, CommandError> {
         logcmd(self, self.kind.cmdline_verbosity());
         self.cmd
@@ -149,6 +261,55 @@ impl CommandPlus {
     }
 }
 
+/// Send each line read from `reader` to `sender` until end of file or an
+/// error occurs
+fn forward_lines<R: Read>(reader: R, stream: Stream, sender: &mpsc::Sender<(Stream, Vec<u8>)>) {
+    let mut reader = BufReader::new(reader);
+    loop {
+        let mut line = Vec::new();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {
+                if sender.send((stream, line)).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// An output stream of a command
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    /// Write `line` to the corresponding stream of the current process
+    fn echo(self, line: &[u8]) {
+        let _ = match self {
+            Stream::Stdout => io::stdout().write_all(line),
+            Stream::Stderr => io::stderr().write_all(line),
+        };
+    }
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stream::Stdout => write!(f, "stdout"),
+            Stream::Stderr => write!(f, "stderr"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct CapturedOutput {
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) struct CommandLine {
     line: String,
@@ -171,6 +332,11 @@ impl CommandLine {
     fn current_dir(&mut self, cwd: &Path) {
         self.cwd = Some(cwd.to_owned());
     }
+
+    /// Return the quoted command line without the working directory
+    pub(crate) fn line(&self) -> &str {
+        &self.line
+    }
 }
 
 impl fmt::Display for CommandLine {
@@ -193,7 +359,7 @@ pub(crate) enum CommandError {
     #[error("failed to run {cmdline:#}")]
     Startup {
         cmdline: CommandLine,
-        source: std::io::Error,
+        source: io::Error,
     },
     #[error("command {cmdline:#} failed: {rc}")]
     Exit {
@@ -202,6 +368,11 @@ pub(crate) enum CommandError {
         stdout: Option<String>,
         stderr: Option<String>,
     },
+    #[error("failed to write log for {cmdline:#}")]
+    Log {
+        cmdline: CommandLine,
+        source: io::Error,
+    },
 }
 
 impl CommandError {
@@ -225,3 +396,29 @@ impl CommandError {
 fn quote_osstr(s: &OsStr) -> String {
     shell_words::quote(&s.to_string_lossy()).to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_logged() {
+        let mut log = Vec::new();
+        let r = CommandPlus::new("sh")
+            .args(["-c", "echo out; printf err >&2; exit 2"])
+            .kind(CommandKind::Filter)
+            .run_logged(&mut log);
+        assert!(matches!(r, Err(CommandError::Exit { rc, .. }) if rc.code() == Some(2)));
+        let log = String::from_utf8(log).unwrap();
+        let lines = log
+            .lines()
+            .map(|ln| ln.split_once("] ").unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "$ sh -c 'echo out; printf err >&2; exit 2'");
+        assert!(lines.contains(&"stdout: out"));
+        assert!(lines.contains(&"stderr: err"));
+        assert_eq!(lines[3], "exit status: 2");
+    }
+}
@@ -0,0 +1,147 @@
+use super::ForAll;
+use super::runpr::{Change, PrOpts};
+use crate::cmd::{CapturedOutput, CommandLine};
+use crate::logging::logproject;
+use crate::project::{DependencyBump, Language, Project};
+use clap::Args;
+use fs_err::PathExt;
+use std::fmt::Write;
+
+/// Update the version requirement on a dependency in each project
+///
+/// For Rust projects, the requirements in the `dependencies`,
+/// `dev-dependencies`, `build-dependencies` (including target-specific ones),
+/// and `workspace.dependencies` tables of the `Cargo.toml` files of the
+/// project and its workspace members are updated, after which `Cargo.lock`
+/// (if present) is updated with `cargo update -p NAME`.
+///
+/// For Python projects, the requirements in the `project.dependencies`,
+/// `project.optional-dependencies`, and `dependency-groups` arrays and in
+/// Poetry's dependency tables in `pyproject.toml` are updated.
+///
+/// If VERSION starts with a comparison operator, it is used as the new
+/// requirement as-is.  Otherwise, it replaces the version in the lower-bound
+/// clause of each old requirement, keeping the clause's operator and any
+/// other clauses.  Requirements that cannot be updated this way without
+/// excluding VERSION cause an error.
+///
+/// Manifests are edited in place, preserving formatting and comments.
+/// Projects that do not depend on the package are skipped.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct BumpDep {
+    /// Commit the changes and submit them as pull requests in the same way as
+    /// `run-pr`.  The `run-pr` options for configuring the pull requests can
+    /// only be used with this option.
+    #[arg(long)]
+    pr: bool,
+
+    /// Commit message for `--pr`.  Defaults to "Bump NAME to VERSION".
+    #[arg(short, long, value_name = "TEXT", requires = "pr")]
+    message: Option<String>,
+
+    #[command(flatten)]
+    pr_opts: PrOpts,
+
+    /// Name of the dependency
+    name: String,
+
+    /// New version requirement
+    version: String,
+}
+
+impl BumpDep {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let bumper = DepBumper {
+            name: self.name,
+            version: self.version,
+        };
+        if self.pr {
+            let message = self
+                .message
+                .unwrap_or_else(|| format!("Bump {} to {}", bumper.name, bumper.version));
+            self.pr_opts.into_forall(&message, bumper)
+        } else if self.pr_opts != PrOpts::default() {
+            anyhow::bail!("pull request options require --pr");
+        } else {
+            Ok(Box::new(bumper))
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct DepBumper {
+    name: String,
+    version: String,
+}
+
+impl DepBumper {
+    /// Write the edited manifests to disk and update the project's lockfile.
+    /// If `capture` is true, descriptions of the changes and the output from
+    /// updating the lockfile are returned; otherwise, they are logged.
+    fn save(
+        &self,
+        p: &Project,
+        bump: &DependencyBump,
+        capture: bool,
+    ) -> anyhow::Result<CapturedOutput> {
+        let mut output = CapturedOutput::default();
+        for change in bump.changes() {
+            if capture {
+                let _ = writeln!(output.stdout, "{change}");
+            } else {
+                info!("{change}");
+            }
+        }
+        bump.save()?;
+        if p.language() == Language::Rust && p.dirpath().join("Cargo.lock").fs_err_try_exists()? {
+            let mut cmd = p.runcmd("cargo");
+            cmd.args(["update", "-p"]).arg(&self.name);
+            if capture {
+                let out = cmd.run_capture()?;
+                output.stdout.push_str(&out.stdout);
+                output.stderr.push_str(&out.stderr);
+            } else {
+                cmd.run()?;
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl ForAll for DepBumper {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let bump = p.bump_dependency(&self.name, &self.version)?;
+        if bump.is_empty() {
+            debug!(
+                "{} does not have a requirement on {} to update; skipping",
+                p.name(),
+                self.name
+            );
+            return Ok(());
+        }
+        logproject(p);
+        self.save(p, &bump, false)?;
+        Ok(())
+    }
+}
+
+impl Change for DepBumper {
+    fn apply(&self, p: &Project, capture: bool) -> anyhow::Result<Option<CapturedOutput>> {
+        let bump = p.bump_dependency(&self.name, &self.version)?;
+        let output = if bump.is_empty() {
+            CapturedOutput::default()
+        } else {
+            self.save(p, &bump, capture)?
+        };
+        Ok(capture.then_some(output))
+    }
+
+    fn cmdline(&self, p: &Project) -> CommandLine {
+        p.runcmd("forall")
+            .arg("bump-dep")
+            .arg(&self.name)
+            .arg(&self.version)
+            .cmdline()
+            .clone()
+    }
+}
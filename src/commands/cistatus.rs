@@ -0,0 +1,94 @@
+use super::ForAll;
+use crate::github::{CiStatus, GitHub, RepoCache};
+use crate::project::Project;
+use crate::table::Table;
+use clap::Args;
+use ghrepo::GHRepo;
+use serde::Serialize;
+
+/// Show the CI status of the head of each project's default branch
+///
+/// The status combines the commit statuses and check runs reported to GitHub
+/// for the commit and is one of "pass", "fail", "pending", or "none" (if no
+/// CI has reported anything).
+///
+/// Only projects that have GitHub remotes are considered.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CiStatusCmd {
+    /// Only show projects whose CI is failing
+    #[arg(long)]
+    failing: bool,
+
+    /// Output JSON
+    #[arg(short = 'J', long)]
+    json: bool,
+}
+
+impl CiStatusCmd {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let github = GitHub::authed()?;
+        Ok(Box::new(CiStatusForAll {
+            cache: RepoCache::with_client(github),
+            failing: self.failing,
+            json: self.json,
+            table: Table::new(["PROJECT", "REPOSITORY", "BRANCH", "STATUS"]),
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CiStatusForAll {
+    cache: RepoCache,
+    failing: bool,
+    json: bool,
+    table: Table,
+}
+
+impl ForAll for CiStatusForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let Some(ghrepo) = p.ghrepo() else {
+            debug!("{} does not have a GitHub repository; skipping", p.name());
+            return Ok(());
+        };
+        let status = self.cache.default_branch_ci_status(ghrepo)?;
+        if self.failing && status != CiStatus::Failure {
+            return Ok(());
+        }
+        let details = CiStatusDetails {
+            project: p.name(),
+            repo: ghrepo,
+            branch: &self.cache.get(ghrepo)?.default_branch,
+            status,
+        };
+        if self.json {
+            println!(
+                "{}",
+                serde_json::to_string(&details).expect("JSONification should not fail")
+            );
+        } else {
+            let row = vec![
+                details.project.to_owned(),
+                details.repo.to_string(),
+                details.branch.to_owned(),
+                details.status.to_string(),
+            ];
+            self.table.push(row);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if !self.json && !self.table.is_empty() {
+            print!("{}", self.table);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct CiStatusDetails<'a> {
+    project: &'a str,
+    repo: &'a GHRepo,
+    branch: &'a str,
+    status: CiStatus,
+}
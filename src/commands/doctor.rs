@@ -0,0 +1,86 @@
+use crate::finder::{DiscoveryProblem, ProblemKind};
+use crate::table::Table;
+use clap::Args;
+use serde::Serialize;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// List Git repositories that could not be loaded as projects
+///
+/// Repositories whose manifests could not be read or parsed are listed as
+/// "invalid", and repositories that do not contain a recognized type of
+/// project are listed as "unrecognized".  The command fails if there are any
+/// invalid repositories.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Doctor {
+    /// Output JSON
+    #[arg(short = 'J', long)]
+    json: bool,
+}
+
+impl Doctor {
+    pub(super) fn run(&self, problems: &[DiscoveryProblem]) -> ExitCode {
+        let mut table = Table::new(["DIRECTORY", "PROBLEM", "REASON"]);
+        let mut invalid = false;
+        for prob in problems {
+            let report = match prob.kind {
+                ProblemKind::Invalid(ref e) => {
+                    invalid = true;
+                    Report {
+                        dirpath: &prob.dirpath,
+                        problem: "invalid",
+                        reason: format!("{e:#}"),
+                    }
+                }
+                ProblemKind::Unrecognized => Report {
+                    dirpath: &prob.dirpath,
+                    problem: "unrecognized",
+                    reason: String::from("no Python or Rust project manifest found"),
+                },
+            };
+            if self.json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&report).expect("JSONification should not fail")
+                );
+            } else {
+                // Some errors (e.g., TOML parse errors) span multiple lines,
+                // which would break the table layout
+                let reason = match prob.kind {
+                    ProblemKind::Invalid(ref e) => e
+                        .chain()
+                        .map(|src| {
+                            src.to_string()
+                                .lines()
+                                .next()
+                                .unwrap_or_default()
+                                .to_owned()
+                        })
+                        .collect::<Vec<_>>()
+                        .join(": "),
+                    ProblemKind::Unrecognized => report.reason,
+                };
+                table.push(vec![
+                    report.dirpath.display().to_string(),
+                    report.problem.to_owned(),
+                    reason,
+                ]);
+            }
+        }
+        if !self.json && !table.is_empty() {
+            print!("{table}");
+        }
+        if invalid {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct Report<'a> {
+    dirpath: &'a Path,
+    problem: &'static str,
+    reason: String,
+}
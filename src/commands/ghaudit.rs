@@ -0,0 +1,573 @@
+use super::ForAll;
+use crate::github::{
+    BranchProtection, GitHub, Repository, SetBranchProtection, SetRequiredReviews, SetStatusChecks,
+    StatusCheck, UpdateRepository,
+};
+use crate::logging::logproject;
+use crate::project::Project;
+use anyhow::Context;
+use clap::Args;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Check each project's GitHub repository settings against a policy file
+///
+/// Each setting that deviates from the policy is printed.  Unless `--fix` is
+/// given, the command fails if any repository deviates from the policy.
+///
+/// Only projects that have non-archived GitHub remotes are considered.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct GhAudit {
+    /// Update repository settings to conform to the policy
+    #[arg(long)]
+    fix: bool,
+
+    /// TOML file describing the desired repository settings [required]
+    #[arg(long, required = true, value_name = "FILE")]
+    policy: PathBuf,
+}
+
+impl GhAudit {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let github = GitHub::authed()?;
+        let policy = Policy::load(&self.policy)?;
+        Ok(Box::new(GhAuditForAll {
+            github,
+            policy,
+            fix: self.fix,
+            deviating: 0,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct GhAuditForAll {
+    github: GitHub,
+    policy: Policy,
+    fix: bool,
+    /// Number of repositories found to deviate from the policy
+    deviating: usize,
+}
+
+impl ForAll for GhAuditForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let Some(ghrepo) = p.ghrepo() else {
+            debug!("{} does not have a GitHub repository; skipping", p.name());
+            return Ok(());
+        };
+        let repo = self.github.get_repository(ghrepo)?;
+        if repo.archived {
+            debug!("Repository for {} is archived; skipping", p.name());
+            return Ok(());
+        }
+        let protection = if self.policy.branch_protection.is_some() {
+            self.github
+                .get_branch_protection(ghrepo, &repo.default_branch)?
+        } else {
+            None
+        };
+        let audit = audit(&self.policy, &repo, protection.as_ref());
+        if audit.deviations.is_empty() {
+            debug!("{}: repository conforms to policy", p.name());
+            return Ok(());
+        }
+        self.deviating += 1;
+        logproject(p);
+        for dev in &audit.deviations {
+            println!("{dev}");
+        }
+        if self.fix {
+            let mut branch = repo.default_branch.as_str();
+            if let Some(ref new_branch) = audit.default_branch {
+                if self.github.get_branch_names(ghrepo)?.contains(new_branch) {
+                    self.github.update_repository(
+                        ghrepo,
+                        &UpdateRepository {
+                            default_branch: Some(new_branch.clone()),
+                            ..UpdateRepository::default()
+                        },
+                    )?;
+                } else {
+                    self.github.rename_branch(ghrepo, branch, new_branch)?;
+                }
+                branch = new_branch;
+            }
+            if !audit.update.is_empty() {
+                self.github.update_repository(ghrepo, &audit.update)?;
+            }
+            if let Some(ref protection) = audit.protection {
+                self.github
+                    .set_branch_protection(ghrepo, branch, protection)?;
+            }
+            info!("Fixed {} setting(s) for {ghrepo}", audit.deviations.len());
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if !self.fix && self.deviating > 0 {
+            anyhow::bail!(
+                "{} repositories do not conform to the policy",
+                self.deviating
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Desired repository settings.  Unset settings are not checked.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Policy {
+    default_branch: Option<String>,
+    allow_merge_commit: Option<bool>,
+    allow_squash_merge: Option<bool>,
+    allow_rebase_merge: Option<bool>,
+    delete_branch_on_merge: Option<bool>,
+    has_issues: Option<bool>,
+    has_wiki: Option<bool>,
+    /// If set, the default branch must be protected
+    branch_protection: Option<ProtectionPolicy>,
+}
+
+impl Policy {
+    fn load(path: &Path) -> anyhow::Result<Policy> {
+        let src = fs_err::read_to_string(path)?;
+        toml::from_str(&src).with_context(|| format!("failed to deserialize {}", path.display()))
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct ProtectionPolicy {
+    required_approving_review_count: Option<u32>,
+    /// Names of status checks that must pass before merging
+    required_status_checks: Option<Vec<String>>,
+    /// Whether branches must be up to date before merging
+    strict: Option<bool>,
+    enforce_admins: Option<bool>,
+}
+
+/// A repository setting that does not match the policy
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Deviation {
+    setting: &'static str,
+    actual: String,
+    expected: String,
+}
+
+impl Deviation {
+    fn new<A: fmt::Display, E: fmt::Display>(
+        setting: &'static str,
+        actual: A,
+        expected: E,
+    ) -> Deviation {
+        Deviation {
+            setting,
+            actual: actual.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Deviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (expected {})",
+            self.setting, self.actual, self.expected
+        )
+    }
+}
+
+/// The results of auditing a repository: the deviations from the policy and
+/// the changes needed to fix them
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Audit {
+    deviations: Vec<Deviation>,
+    /// The name that the default branch should be changed to
+    default_branch: Option<String>,
+    update: UpdateRepository,
+    /// The protection settings to apply to the default branch
+    protection: Option<SetBranchProtection>,
+}
+
+fn audit(policy: &Policy, repo: &Repository, protection: Option<&BranchProtection>) -> Audit {
+    let mut audit = Audit::default();
+    if let Some(ref branch) = policy.default_branch
+        && &repo.default_branch != branch
+    {
+        audit.deviations.push(Deviation::new(
+            "default_branch",
+            &repo.default_branch,
+            branch,
+        ));
+        audit.default_branch = Some(branch.clone());
+    }
+    let flags = [
+        (
+            "allow_merge_commit",
+            repo.allow_merge_commit,
+            policy.allow_merge_commit,
+            &mut audit.update.allow_merge_commit,
+        ),
+        (
+            "allow_squash_merge",
+            repo.allow_squash_merge,
+            policy.allow_squash_merge,
+            &mut audit.update.allow_squash_merge,
+        ),
+        (
+            "allow_rebase_merge",
+            repo.allow_rebase_merge,
+            policy.allow_rebase_merge,
+            &mut audit.update.allow_rebase_merge,
+        ),
+        (
+            "delete_branch_on_merge",
+            repo.delete_branch_on_merge,
+            policy.delete_branch_on_merge,
+            &mut audit.update.delete_branch_on_merge,
+        ),
+        (
+            "has_issues",
+            Some(repo.has_issues),
+            policy.has_issues,
+            &mut audit.update.has_issues,
+        ),
+        (
+            "has_wiki",
+            Some(repo.has_wiki),
+            policy.has_wiki,
+            &mut audit.update.has_wiki,
+        ),
+    ];
+    for (setting, actual, expected, update) in flags {
+        if let Some(expected) = expected
+            && actual != Some(expected)
+        {
+            let actual = actual.map_or_else(|| String::from("unknown"), |b| b.to_string());
+            audit
+                .deviations
+                .push(Deviation::new(setting, actual, expected));
+            *update = Some(expected);
+        }
+    }
+    if let Some(ref pp) = policy.branch_protection {
+        let before = audit.deviations.len();
+        let current = protection.cloned().unwrap_or_default();
+        if protection.is_none() {
+            audit
+                .deviations
+                .push(Deviation::new("branch_protection", "disabled", "enabled"));
+        }
+        // Start from the current settings so that protections not covered
+        // by the policy are left as they are
+        let mut desired = SetBranchProtection::from(&current);
+        if let Some(count) = pp.required_approving_review_count {
+            let actual = current
+                .required_pull_request_reviews
+                .as_ref()
+                .map(|r| r.required_approving_review_count);
+            if actual != Some(count) {
+                audit.deviations.push(Deviation::new(
+                    "branch_protection.required_approving_review_count",
+                    actual.map_or_else(|| String::from("none"), |n| n.to_string()),
+                    count,
+                ));
+                desired
+                    .required_pull_request_reviews
+                    .get_or_insert_with(SetRequiredReviews::default)
+                    .required_approving_review_count = count;
+            }
+        }
+        if let Some(ref checks) = pp.required_status_checks {
+            let mut actual = current
+                .required_status_checks
+                .as_ref()
+                .map(|c| c.contexts.clone())
+                .unwrap_or_default();
+            actual.sort();
+            let mut expected = checks.clone();
+            expected.sort();
+            if actual != expected {
+                audit.deviations.push(Deviation::new(
+                    "branch_protection.required_status_checks",
+                    format!("{actual:?}"),
+                    format!("{expected:?}"),
+                ));
+                let sc = desired
+                    .required_status_checks
+                    .get_or_insert_with(SetStatusChecks::default);
+                // Keep the required app for checks that are already present
+                sc.checks = expected
+                    .into_iter()
+                    .map(|context| {
+                        sc.checks
+                            .iter()
+                            .find(|c| c.context == context)
+                            .cloned()
+                            .unwrap_or(StatusCheck {
+                                context,
+                                app_id: None,
+                            })
+                    })
+                    .collect();
+            }
+        }
+        if let Some(strict) = pp.strict {
+            let actual = current.required_status_checks.as_ref().map(|c| c.strict);
+            if actual != Some(strict) {
+                audit.deviations.push(Deviation::new(
+                    "branch_protection.strict",
+                    actual.map_or_else(|| String::from("none"), |b| b.to_string()),
+                    strict,
+                ));
+                desired
+                    .required_status_checks
+                    .get_or_insert_with(SetStatusChecks::default)
+                    .strict = strict;
+            }
+        }
+        if let Some(enforce) = pp.enforce_admins {
+            let actual = current.enforce_admins.map(|e| e.enabled);
+            if actual != Some(enforce) {
+                audit.deviations.push(Deviation::new(
+                    "branch_protection.enforce_admins",
+                    actual.map_or_else(|| String::from("none"), |b| b.to_string()),
+                    enforce,
+                ));
+                desired.enforce_admins = Some(enforce);
+            }
+        }
+        if audit.deviations.len() > before {
+            audit.protection = Some(desired);
+        }
+    }
+    audit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn repository(default_branch: &str, has_wiki: bool, squash: Option<bool>) -> Repository {
+        serde_json::from_value(json!({
+            "id": 1,
+            "name": "repo",
+            "full_name": "owner/repo",
+            "url": "https://api.github.com/repos/owner/repo",
+            "private": false,
+            "archived": false,
+            "fork": false,
+            "html_url": "https://github.com/owner/repo",
+            "ssh_url": "git@github.com:owner/repo.git",
+            "clone_url": "https://github.com/owner/repo.git",
+            "default_branch": default_branch,
+            "has_issues": true,
+            "has_wiki": has_wiki,
+            "allow_squash_merge": squash,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_audit_conforming() {
+        let policy = Policy {
+            default_branch: Some(String::from("main")),
+            has_issues: Some(true),
+            has_wiki: Some(false),
+            ..Policy::default()
+        };
+        let repo = repository("main", false, None);
+        assert_eq!(audit(&policy, &repo, None), Audit::default());
+    }
+
+    #[test]
+    fn test_audit_settings() {
+        let policy = Policy {
+            default_branch: Some(String::from("main")),
+            allow_squash_merge: Some(true),
+            delete_branch_on_merge: Some(true),
+            has_wiki: Some(false),
+            ..Policy::default()
+        };
+        let repo = repository("master", true, Some(true));
+        let result = audit(&policy, &repo, None);
+        assert_eq!(
+            result
+                .deviations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "default_branch: master (expected main)",
+                "delete_branch_on_merge: unknown (expected true)",
+                "has_wiki: true (expected false)",
+            ]
+        );
+        assert_eq!(result.default_branch.as_deref(), Some("main"));
+        assert_eq!(
+            result.update,
+            UpdateRepository {
+                delete_branch_on_merge: Some(true),
+                has_wiki: Some(false),
+                ..UpdateRepository::default()
+            }
+        );
+        assert_eq!(result.protection, None);
+    }
+
+    #[test]
+    fn test_audit_unprotected() {
+        let policy = Policy {
+            branch_protection: Some(ProtectionPolicy {
+                required_approving_review_count: Some(1),
+                required_status_checks: Some(vec![String::from("test")]),
+                ..ProtectionPolicy::default()
+            }),
+            ..Policy::default()
+        };
+        let repo = repository("main", false, None);
+        let result = audit(&policy, &repo, None);
+        assert_eq!(result.deviations.len(), 3);
+        assert_eq!(
+            result.protection,
+            Some(SetBranchProtection {
+                required_status_checks: Some(SetStatusChecks {
+                    strict: false,
+                    checks: vec![StatusCheck {
+                        context: String::from("test"),
+                        app_id: None,
+                    }],
+                }),
+                required_pull_request_reviews: Some(SetRequiredReviews {
+                    required_approving_review_count: 1,
+                    ..SetRequiredReviews::default()
+                }),
+                ..SetBranchProtection::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_audit_protection_partial() {
+        let policy = Policy {
+            branch_protection: Some(ProtectionPolicy {
+                required_approving_review_count: Some(1),
+                enforce_admins: Some(true),
+                ..ProtectionPolicy::default()
+            }),
+            ..Policy::default()
+        };
+        let repo = repository("main", false, None);
+        let protection: BranchProtection = serde_json::from_value(json!({
+            "required_status_checks": {"strict": true, "contexts": ["lint"]},
+            "enforce_admins": {"enabled": false},
+            "required_pull_request_reviews": {"required_approving_review_count": 1},
+        }))
+        .unwrap();
+        let result = audit(&policy, &repo, Some(&protection));
+        assert_eq!(
+            result.deviations,
+            [Deviation::new(
+                "branch_protection.enforce_admins",
+                false,
+                true
+            )]
+        );
+        assert_eq!(
+            result.protection,
+            Some(SetBranchProtection {
+                required_status_checks: Some(SetStatusChecks {
+                    strict: true,
+                    checks: vec![StatusCheck {
+                        context: String::from("lint"),
+                        app_id: None,
+                    }],
+                }),
+                enforce_admins: Some(true),
+                required_pull_request_reviews: Some(SetRequiredReviews {
+                    required_approving_review_count: 1,
+                    ..SetRequiredReviews::default()
+                }),
+                ..SetBranchProtection::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_audit_protection_preserves_unmanaged() {
+        let policy = Policy {
+            branch_protection: Some(ProtectionPolicy {
+                required_approving_review_count: Some(2),
+                required_status_checks: Some(vec![String::from("lint"), String::from("test")]),
+                ..ProtectionPolicy::default()
+            }),
+            ..Policy::default()
+        };
+        let repo = repository("main", false, None);
+        let protection: BranchProtection = serde_json::from_value(json!({
+            "required_status_checks": {
+                "strict": true,
+                "contexts": ["lint"],
+                "checks": [{"context": "lint", "app_id": 15368}],
+            },
+            "enforce_admins": {"enabled": true},
+            "required_pull_request_reviews": {
+                "dismiss_stale_reviews": true,
+                "require_code_owner_reviews": true,
+                "required_approving_review_count": 1,
+                "dismissal_restrictions": {
+                    "users": [{"login": "alice"}],
+                    "teams": [],
+                    "apps": [],
+                },
+            },
+            "restrictions": {
+                "users": [{"login": "bob"}],
+                "teams": [{"slug": "maintainers"}],
+                "apps": [],
+            },
+            "required_linear_history": {"enabled": true},
+            "allow_force_pushes": {"enabled": false},
+        }))
+        .unwrap();
+        let result = audit(&policy, &repo, Some(&protection));
+        assert_eq!(result.deviations.len(), 2);
+        let desired = result.protection.unwrap();
+        assert_eq!(
+            serde_json::to_value(&desired).unwrap(),
+            json!({
+                "required_status_checks": {
+                    "strict": true,
+                    "checks": [
+                        {"context": "lint", "app_id": 15368},
+                        {"context": "test"},
+                    ],
+                },
+                "enforce_admins": true,
+                "required_pull_request_reviews": {
+                    "required_approving_review_count": 2,
+                    "dismiss_stale_reviews": true,
+                    "require_code_owner_reviews": true,
+                    "require_last_push_approval": false,
+                    "dismissal_restrictions": {
+                        "users": ["alice"],
+                        "teams": [],
+                        "apps": [],
+                    },
+                },
+                "restrictions": {
+                    "users": ["bob"],
+                    "teams": ["maintainers"],
+                    "apps": [],
+                },
+                "required_linear_history": true,
+                "allow_force_pushes": false,
+            })
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use super::ForAll;
+use crate::github::{GitHub, ReviewStatus};
+use crate::project::Project;
+use crate::table::Table;
+use clap::{Args, ValueEnum};
+use ghrepo::GHRepo;
+use serde::Serialize;
+use std::cmp::Ordering;
+
+/// Show a summary of each project's GitHub repository
+///
+/// For each repository, the number of open issues, the number of open pull
+/// requests, the oldest open non-draft pull request that has not received any
+/// reviews, and the tag of the latest release are shown.
+///
+/// Only projects that have GitHub remotes are considered.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct GhSummary {
+    /// Output CSV
+    #[arg(long, conflicts_with = "json")]
+    csv: bool,
+
+    /// Output JSON
+    #[arg(short = 'J', long)]
+    json: bool,
+
+    /// Sort the output by the given column.  Numeric columns are sorted in
+    /// descending order, others in ascending order.  [default: project]
+    #[arg(short, long, value_enum, value_name = "COLUMN")]
+    sort: Option<SortKey>,
+
+    /// Reverse the sort order
+    #[arg(short, long)]
+    reverse: bool,
+}
+
+impl GhSummary {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let github = GitHub::authed()?;
+        Ok(Box::new(GhSummaryForAll {
+            github,
+            format: if self.json {
+                Format::Json
+            } else if self.csv {
+                Format::Csv
+            } else {
+                Format::Table
+            },
+            sort: self.sort.unwrap_or(SortKey::Project),
+            reverse: self.reverse,
+            summaries: Vec::new(),
+        }))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum SortKey {
+    Project,
+    Repository,
+    Issues,
+    Prs,
+    /// Sort by the creation time of the oldest unreviewed pull request
+    Unreviewed,
+    Release,
+}
+
+#[derive(Clone, Debug)]
+struct GhSummaryForAll {
+    github: GitHub,
+    format: Format,
+    sort: SortKey,
+    reverse: bool,
+    summaries: Vec<RepoSummary>,
+}
+
+impl ForAll for GhSummaryForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let Some(ghrepo) = p.ghrepo() else {
+            debug!("{} does not have a GitHub repository; skipping", p.name());
+            return Ok(());
+        };
+        let repo = self.github.get_repository(ghrepo)?;
+        let mut prs = self.github.list_open_pull_requests(ghrepo)?;
+        prs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        let mut oldest_unreviewed = None;
+        for pr in prs.iter().filter(|pr| !pr.draft) {
+            if self.github.get_review_status(ghrepo, pr.number)? == ReviewStatus::None {
+                oldest_unreviewed = Some(UnreviewedPr {
+                    number: pr.number,
+                    html_url: pr.html_url.clone(),
+                    created_at: pr.created_at.clone(),
+                });
+                break;
+            }
+        }
+        let latest_release = self
+            .github
+            .get_latest_release(ghrepo)?
+            .map(|rel| rel.tag_name);
+        let open_prs = u64::try_from(prs.len()).unwrap_or(u64::MAX);
+        self.summaries.push(RepoSummary {
+            project: p.name().to_owned(),
+            repo: ghrepo.clone(),
+            // GitHub counts pull requests as issues
+            open_issues: repo.open_issues_count.saturating_sub(open_prs),
+            open_prs,
+            oldest_unreviewed,
+            latest_release,
+        });
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        let key = self.sort;
+        self.summaries.sort_by(|a, b| a.compare(b, key));
+        if self.reverse {
+            self.summaries.reverse();
+        }
+        match self.format {
+            Format::Table => {
+                if !self.summaries.is_empty() {
+                    let mut table = Table::new(HEADERS);
+                    for s in &self.summaries {
+                        table.push(s.to_row());
+                    }
+                    print!("{table}");
+                }
+            }
+            Format::Json => {
+                for s in &self.summaries {
+                    println!(
+                        "{}",
+                        serde_json::to_string(s).expect("JSONification should not fail")
+                    );
+                }
+            }
+            Format::Csv => {
+                println!("{}", csv_line(HEADERS));
+                for s in &self.summaries {
+                    println!("{}", csv_line(s.to_row()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+const HEADERS: [&str; 6] = [
+    "PROJECT",
+    "REPOSITORY",
+    "ISSUES",
+    "PRS",
+    "OLDEST UNREVIEWED",
+    "RELEASE",
+];
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct RepoSummary {
+    project: String,
+    repo: GHRepo,
+    open_issues: u64,
+    open_prs: u64,
+    oldest_unreviewed: Option<UnreviewedPr>,
+    latest_release: Option<String>,
+}
+
+impl RepoSummary {
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.project.clone(),
+            self.repo.to_string(),
+            self.open_issues.to_string(),
+            self.open_prs.to_string(),
+            self.oldest_unreviewed
+                .as_ref()
+                .map_or_else(|| String::from("-"), |pr| format!("#{}", pr.number)),
+            self.latest_release
+                .clone()
+                .unwrap_or_else(|| String::from("-")),
+        ]
+    }
+
+    /// Compare two summaries by the given column, falling back to the project
+    /// name for ties.  Missing values sort after present ones.
+    fn compare(&self, other: &RepoSummary, key: SortKey) -> Ordering {
+        let ord = match key {
+            SortKey::Project => Ordering::Equal,
+            SortKey::Repository => self
+                .repo
+                .to_string()
+                .to_ascii_lowercase()
+                .cmp(&other.repo.to_string().to_ascii_lowercase()),
+            SortKey::Issues => other.open_issues.cmp(&self.open_issues),
+            SortKey::Prs => other.open_prs.cmp(&self.open_prs),
+            SortKey::Unreviewed => cmp_present_first(
+                self.oldest_unreviewed.as_ref().map(|pr| &pr.created_at),
+                other.oldest_unreviewed.as_ref().map(|pr| &pr.created_at),
+            ),
+            SortKey::Release => {
+                cmp_present_first(self.latest_release.as_ref(), other.latest_release.as_ref())
+            }
+        };
+        ord.then_with(|| self.project.cmp(&other.project))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct UnreviewedPr {
+    number: u64,
+    html_url: String,
+    created_at: String,
+}
+
+fn cmp_present_first<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Format a row of fields as a line of CSV, quoting fields as necessary
+fn csv_line<I, S>(fields: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    fields
+        .into_iter()
+        .map(|f| {
+            let f = f.as_ref();
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(
+        project: &str,
+        issues: u64,
+        unreviewed: Option<&str>,
+        release: Option<&str>,
+    ) -> RepoSummary {
+        RepoSummary {
+            project: project.to_owned(),
+            repo: GHRepo::new("octocat", project).unwrap(),
+            open_issues: issues,
+            open_prs: 0,
+            oldest_unreviewed: unreviewed.map(|created_at| UnreviewedPr {
+                number: 1,
+                html_url: format!("https://github.com/octocat/{project}/pull/1"),
+                created_at: created_at.to_owned(),
+            }),
+            latest_release: release.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut summaries = vec![
+            summary("foo", 3, None, Some("v1.0.0")),
+            summary("bar", 3, Some("2025-03-01T00:00:00Z"), None),
+            summary("baz", 10, Some("2024-12-31T00:00:00Z"), Some("v0.1.0")),
+        ];
+        let names = |summaries: &[RepoSummary]| {
+            summaries
+                .iter()
+                .map(|s| s.project.clone())
+                .collect::<Vec<_>>()
+        };
+        summaries.sort_by(|a, b| a.compare(b, SortKey::Project));
+        assert_eq!(names(&summaries), ["bar", "baz", "foo"]);
+        summaries.sort_by(|a, b| a.compare(b, SortKey::Issues));
+        assert_eq!(names(&summaries), ["baz", "bar", "foo"]);
+        summaries.sort_by(|a, b| a.compare(b, SortKey::Unreviewed));
+        assert_eq!(names(&summaries), ["baz", "bar", "foo"]);
+        summaries.sort_by(|a, b| a.compare(b, SortKey::Release));
+        assert_eq!(names(&summaries), ["baz", "foo", "bar"]);
+    }
+
+    #[test]
+    fn test_csv_line() {
+        assert_eq!(
+            csv_line(["plain", "with,comma", "with \"quotes\"", ""]),
+            "plain,\"with,comma\",\"with \"\"quotes\"\"\","
+        );
+    }
+}
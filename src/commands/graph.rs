@@ -0,0 +1,56 @@
+use super::ForAll;
+use crate::depgraph::DepGraph;
+use crate::project::Project;
+use clap::{Args, ValueEnum};
+
+/// Output the graph of dependencies between the selected projects
+///
+/// There is an edge from project A to project B if A depends on a package
+/// provided by B.  Dependencies on packages not provided by any of the
+/// selected projects are omitted.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Graph {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Dot)]
+    format: Format,
+}
+
+impl Graph {
+    pub(super) fn into_forall(self) -> Box<dyn ForAll> {
+        Box::new(GraphForAll {
+            format: self.format,
+            projects: Vec::new(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+enum Format {
+    Dot,
+    Json,
+}
+
+#[derive(Clone, Debug)]
+struct GraphForAll {
+    format: Format,
+    projects: Vec<Project>,
+}
+
+impl ForAll for GraphForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        self.projects.push(p.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        let graph = DepGraph::build(&self.projects)?;
+        match self.format {
+            Format::Dot => print!("{}", graph.to_dot()),
+            Format::Json => println!(
+                "{}",
+                serde_json::to_string(&graph.to_json()).expect("JSONification should not fail")
+            ),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,396 @@
+use super::ForAll;
+use crate::github::{CreateLabel, GitHub, LabelInfo, UpdateLabel};
+use crate::logging::logproject;
+use crate::project::Project;
+use anyhow::Context;
+use clap::{Args, Subcommand};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Manage issue & pull request labels across GitHub repositories
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Labels {
+    #[command(subcommand)]
+    command: LabelsCommand,
+}
+
+impl Labels {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        match self.command {
+            LabelsCommand::Sync(c) => c.into_forall(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
+enum LabelsCommand {
+    Sync(LabelsSync),
+}
+
+/// Make each project's GitHub repository's labels match those declared in a
+/// TOML file
+///
+/// Declared labels that do not exist are created, existing labels whose
+/// colors or descriptions differ from the declarations are updated, and
+/// labels listed in a declaration's `rename_from` are renamed.
+///
+/// Only projects that have non-archived GitHub remotes are considered.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+struct LabelsSync {
+    /// Also delete labels that are not declared in the file
+    #[arg(long)]
+    delete: bool,
+
+    /// Show what changes would be made without making them
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+
+    /// TOML file declaring the labels [required]
+    #[arg(long, required = true, value_name = "FILE")]
+    from: PathBuf,
+}
+
+impl LabelsSync {
+    fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let github = GitHub::authed()?;
+        let labels = LabelSpec::load(&self.from)?;
+        Ok(Box::new(LabelsSyncForAll {
+            github,
+            labels,
+            delete: self.delete,
+            dry_run: self.dry_run,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct LabelsSyncForAll {
+    github: GitHub,
+    labels: Vec<LabelSpec>,
+    delete: bool,
+    dry_run: bool,
+}
+
+impl ForAll for LabelsSyncForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let Some(ghrepo) = p.ghrepo() else {
+            debug!("{} does not have a GitHub repository; skipping", p.name());
+            return Ok(());
+        };
+        if self.github.get_repository(ghrepo)?.archived {
+            debug!("Repository for {} is archived; skipping", p.name());
+            return Ok(());
+        }
+        let existing = self.github.get_labels(ghrepo)?;
+        let changes = plan_sync(&self.labels, &existing, self.delete);
+        if changes.is_empty() {
+            debug!("{}: labels are up to date", p.name());
+            return Ok(());
+        }
+        logproject(p);
+        for change in changes {
+            println!("{change}");
+            if self.dry_run {
+                continue;
+            }
+            match change {
+                LabelChange::Create(spec) => self.github.create_label(
+                    ghrepo,
+                    CreateLabel {
+                        name: Cow::from(&spec.name),
+                        color: Cow::from(&spec.color),
+                        description: spec.description.as_deref().map(Cow::from),
+                    },
+                )?,
+                LabelChange::Update { current, spec } => self.github.update_label(
+                    ghrepo,
+                    &current.name,
+                    UpdateLabel {
+                        new_name: (current.name != spec.name).then(|| Cow::from(&spec.name)),
+                        color: Some(Cow::from(&spec.color)),
+                        description: spec.description.as_deref().map(Cow::from),
+                    },
+                )?,
+                LabelChange::Delete(label) => self.github.delete_label(ghrepo, &label.name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A label declaration
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct LabelSpec {
+    name: String,
+
+    /// Six-digit hex RGB color, normalized to lowercase without a leading `#`
+    color: String,
+
+    /// The label's description.  If not set, existing descriptions are left
+    /// as-is.
+    description: Option<String>,
+
+    /// Names of existing labels that should be renamed to this label
+    #[serde(default)]
+    rename_from: Vec<String>,
+}
+
+impl LabelSpec {
+    /// Read label declarations from a TOML file containing a `[[label]]`
+    /// table for each label
+    fn load(path: &Path) -> anyhow::Result<Vec<LabelSpec>> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct LabelsFile {
+            #[serde(default)]
+            label: Vec<LabelSpec>,
+        }
+
+        let src = fs_err::read_to_string(path)?;
+        let mut labels = toml::from_str::<LabelsFile>(&src)
+            .with_context(|| format!("failed to deserialize {}", path.display()))?
+            .label;
+        let mut seen = HashSet::new();
+        for spec in &mut labels {
+            let color = spec.color.strip_prefix('#').unwrap_or(&spec.color);
+            if color.len() != 6 || !color.chars().all(|c| c.is_ascii_hexdigit()) {
+                anyhow::bail!(
+                    "{}: label {:?} has invalid color {:?}; expected six hex digits",
+                    path.display(),
+                    spec.name,
+                    spec.color
+                );
+            }
+            spec.color = color.to_ascii_lowercase();
+            for name in std::iter::once(&spec.name).chain(&spec.rename_from) {
+                if !seen.insert(name.to_lowercase()) {
+                    anyhow::bail!("{}: label {name:?} declared twice", path.display());
+                }
+            }
+        }
+        Ok(labels)
+    }
+}
+
+/// A change to make to a repository's labels
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum LabelChange<'a> {
+    Create(&'a LabelSpec),
+    /// Change the name, color, and/or description of an existing label
+    Update {
+        current: &'a LabelInfo,
+        spec: &'a LabelSpec,
+    },
+    Delete(&'a LabelInfo),
+}
+
+impl fmt::Display for LabelChange<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelChange::Create(spec) => {
+                write!(f, "+ {:?} color={}", spec.name, spec.color)?;
+                if let Some(ref desc) = spec.description {
+                    write!(f, " description={desc:?}")?;
+                }
+                Ok(())
+            }
+            LabelChange::Update { current, spec } => {
+                write!(f, "~ {:?}", current.name)?;
+                if current.name != spec.name {
+                    write!(f, " name: {:?} -> {:?}", current.name, spec.name)?;
+                }
+                if !current.color.eq_ignore_ascii_case(&spec.color) {
+                    write!(f, " color: {} -> {}", current.color, spec.color)?;
+                }
+                if let Some(ref desc) = spec.description
+                    && current.description.as_deref().unwrap_or_default() != desc
+                {
+                    write!(
+                        f,
+                        " description: {:?} -> {desc:?}",
+                        current.description.as_deref().unwrap_or_default()
+                    )?;
+                }
+                Ok(())
+            }
+            LabelChange::Delete(label) => write!(f, "- {:?}", label.name),
+        }
+    }
+}
+
+/// Determine the changes needed to make the labels in `existing` match the
+/// declarations in `declared`.  Label names are compared case-insensitively,
+/// as on GitHub.  If `delete` is true, existing labels that are neither
+/// declared nor renamed are deleted.
+fn plan_sync<'a>(
+    declared: &'a [LabelSpec],
+    existing: &'a [LabelInfo],
+    delete: bool,
+) -> Vec<LabelChange<'a>> {
+    let find = |name: &str| {
+        existing
+            .iter()
+            .find(|lbl| lbl.name.to_lowercase() == name.to_lowercase())
+    };
+    let mut changes = Vec::new();
+    let mut matched = HashSet::new();
+    for spec in declared {
+        let current = find(&spec.name).or_else(|| {
+            spec.rename_from
+                .iter()
+                .find_map(|old| find(old).filter(|lbl| !matched.contains(&lbl.name)))
+        });
+        match current {
+            Some(current) => {
+                matched.insert(&current.name);
+                let up_to_date = current.name == spec.name
+                    && current.color.eq_ignore_ascii_case(&spec.color)
+                    && spec.description.as_ref().is_none_or(|desc| {
+                        current.description.as_deref().unwrap_or_default() == desc
+                    });
+                if !up_to_date {
+                    changes.push(LabelChange::Update { current, spec });
+                }
+            }
+            None => changes.push(LabelChange::Create(spec)),
+        }
+    }
+    if delete {
+        for lbl in existing {
+            if !matched.contains(&lbl.name) {
+                changes.push(LabelChange::Delete(lbl));
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str, color: &str, description: Option<&str>, rename_from: &[&str]) -> LabelSpec {
+        LabelSpec {
+            name: name.to_owned(),
+            color: color.to_owned(),
+            description: description.map(String::from),
+            rename_from: rename_from.iter().map(|&s| String::from(s)).collect(),
+        }
+    }
+
+    fn label(name: &str, color: &str, description: Option<&str>) -> LabelInfo {
+        LabelInfo {
+            name: name.to_owned(),
+            color: color.to_owned(),
+            description: description.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_plan_sync() {
+        let declared = vec![
+            spec("bug", "d73a4a", Some("Something isn't working"), &[]),
+            spec("dependencies", "0366d6", None, &["deps"]),
+            spec("enhancement", "a2eeef", Some("New feature"), &[]),
+            spec("documentation", "0075ca", None, &["docs"]),
+        ];
+        let existing = vec![
+            label("Bug", "D73A4A", Some("Something isn't working")),
+            label("deps", "0366d6", Some("Dependency updates")),
+            label("enhancement", "84b6eb", Some("New feature")),
+            label("wontfix", "ffffff", None),
+        ];
+        let changes = plan_sync(&declared, &existing, false);
+        assert_eq!(
+            changes,
+            [
+                LabelChange::Update {
+                    current: &existing[0],
+                    spec: &declared[0],
+                },
+                LabelChange::Update {
+                    current: &existing[1],
+                    spec: &declared[1],
+                },
+                LabelChange::Update {
+                    current: &existing[2],
+                    spec: &declared[2],
+                },
+                LabelChange::Create(&declared[3]),
+            ]
+        );
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                r#"~ "Bug" name: "Bug" -> "bug""#,
+                r#"~ "deps" name: "deps" -> "dependencies""#,
+                r#"~ "enhancement" color: 84b6eb -> a2eeef"#,
+                r#"+ "documentation" color=0075ca"#,
+            ]
+        );
+        let changes = plan_sync(&declared, &existing, true);
+        assert_eq!(changes.last(), Some(&LabelChange::Delete(&existing[3])));
+        assert_eq!(changes.len(), 5);
+    }
+
+    #[test]
+    fn test_plan_sync_up_to_date() {
+        let declared = vec![
+            spec("bug", "d73a4a", Some("Something isn't working"), &[]),
+            spec("dependencies", "0366d6", None, &["deps"]),
+        ];
+        let existing = vec![
+            label("bug", "d73a4a", Some("Something isn't working")),
+            label("dependencies", "0366d6", Some("Dependency updates")),
+            label("deps", "cccccc", None),
+        ];
+        assert_eq!(plan_sync(&declared, &existing, false), []);
+        assert_eq!(
+            plan_sync(&declared, &existing, true),
+            [LabelChange::Delete(&existing[2])]
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("labels.toml");
+        fs_err::write(
+            &path,
+            concat!(
+                "[[label]]\n",
+                "name = \"bug\"\n",
+                "color = \"#D73A4A\"\n",
+                "description = \"Something isn't working\"\n",
+                "\n",
+                "[[label]]\n",
+                "name = \"dependencies\"\n",
+                "color = \"0366d6\"\n",
+                "rename_from = [\"deps\"]\n",
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            LabelSpec::load(&path).unwrap(),
+            [
+                spec("bug", "d73a4a", Some("Something isn't working"), &[]),
+                spec("dependencies", "0366d6", None, &["deps"]),
+            ]
+        );
+        fs_err::write(&path, "[[label]]\nname = \"bug\"\ncolor = \"red\"\n").unwrap();
+        assert!(LabelSpec::load(&path).is_err());
+        fs_err::write(
+            &path,
+            concat!(
+                "[[label]]\nname = \"bug\"\ncolor = \"d73a4a\"\n",
+                "[[label]]\nname = \"BUG\"\ncolor = \"d73a4a\"\n",
+            ),
+        )
+        .unwrap();
+        assert!(LabelSpec::load(&path).is_err());
+    }
+}
@@ -8,6 +8,10 @@ pub(crate) struct List {
     /// Output JSON
     #[arg(short = 'J', long)]
     json: bool,
+
+    /// Include the members of Rust workspaces in the JSON output
+    #[arg(long, requires = "json")]
+    members: bool,
 }
 
 impl ForAll for List {
@@ -15,7 +19,8 @@ impl ForAll for List {
         if self.json {
             println!(
                 "{}",
-                serde_json::to_string(&p.to_details()?).expect("JSONification should not fail")
+                serde_json::to_string(&p.to_details(self.members)?)
+                    .expect("JSONification should not fail")
             );
         } else {
             println!("{}", p.name());
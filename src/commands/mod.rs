@@ -1,91 +1,232 @@
+mod bumpdep;
+mod cistatus;
 mod clean;
 mod cloc;
+mod doctor;
 mod gc;
+mod ghaudit;
+mod ghsummary;
+mod graph;
+mod labels;
 mod list;
+mod outdated;
+mod prclose;
 mod preupdate;
+mod prmerge;
+mod prs;
+mod prstatus;
+mod prunebranches;
 mod pull;
 mod push;
+mod query;
+mod release;
 mod rsclean;
 mod run;
 mod runpr;
+mod versions;
+use self::bumpdep::BumpDep;
+use self::cistatus::CiStatusCmd;
 use self::clean::Clean;
 use self::cloc::Cloc;
+use self::doctor::Doctor;
 use self::gc::Gc;
+use self::ghaudit::GhAudit;
+use self::ghsummary::GhSummary;
+use self::graph::Graph;
+use self::labels::Labels;
 use self::list::List;
+use self::outdated::Outdated;
+use self::prclose::PrClose;
 use self::preupdate::PreUpdate;
+use self::prmerge::PrMerge;
+use self::prstatus::PrStatus;
+use self::prunebranches::PruneBranches;
 use self::pull::Pull;
 use self::push::Push;
+use self::query::Query;
+use self::release::Release;
 use self::rsclean::Rsclean;
 pub(crate) use self::run::Run;
 use self::runpr::RunPr;
-use crate::logging::logerror;
-use crate::project::Project;
+use self::versions::Versions;
+use crate::finder::{Discovery, ProblemKind};
+use crate::logging::{logerror, logwarning};
+use crate::project::{Checkpoint, Project};
 use crate::util::Options;
+use anyhow::Context;
 use clap::Subcommand;
 use std::process::ExitCode;
 
 trait ForAll {
     fn run(&mut self, p: &Project) -> anyhow::Result<()>;
+
+    /// Called after all projects have been operated on, e.g., in order to
+    /// output aggregated results
+    fn finish(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Restore the project to the state recorded in `checkpoint` after an
+/// operation with result `r`, returning the error from the operation or, if
+/// it succeeded, any error from restoring.  If both the operation and
+/// restoring fail, the restoration error is logged.
+///
+/// If the operation failed, any uncommitted changes it left behind are
+/// stashed first so that they are not carried over to the restored branch or
+/// mixed with the restored stash.
+fn restore_after(
+    p: &Project,
+    checkpoint: &Checkpoint,
+    r: anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let restored = if r.is_err() {
+        stash_leftovers(p)
+    } else {
+        Ok(())
+    }
+    .and_then(|()| p.restore(checkpoint))
+    .with_context(|| format!("Failed to restore previous state of {}", p.name()));
+    match (r, restored) {
+        (Err(e), Err(e2)) => {
+            logerror(e2);
+            Err(e)
+        }
+        (Err(e), Ok(())) | (Ok(()), Err(e)) => Err(e),
+        (Ok(()), Ok(())) => Ok(()),
+    }
+}
+
+/// Stash the uncommitted changes left behind by a failed operation, if any
+fn stash_leftovers(p: &Project) -> anyhow::Result<()> {
+    if let Some(oid) = p.stash()? {
+        logwarning(&anyhow::anyhow!(
+            "Changes left by failed operation on {} were stashed as {oid}",
+            p.name()
+        ));
+    }
+    Ok(())
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
 pub(crate) enum Command {
     List(List),
+    BumpDep(BumpDep),
+    CiStatus(CiStatusCmd),
     Clean(Clean),
     Cloc(Cloc),
+    Doctor(Doctor),
     Gc(Gc),
+    GhAudit(GhAudit),
+    GhSummary(GhSummary),
+    Graph(Graph),
+    Labels(Labels),
+    Outdated(Outdated),
     PreUpdate(PreUpdate),
+    PrClose(PrClose),
+    PrMerge(PrMerge),
+    PrStatus(PrStatus),
+    PruneBranches(PruneBranches),
     Pull(Pull),
     Push(Push),
+    Query(Query),
+    Release(Release),
     Rsclean(Rsclean),
     Run(Run),
     RunPr(RunPr),
+    Versions(Versions),
 }
 
 impl Command {
-    pub(crate) fn run(self, opts: Options, projects: Vec<Project>) -> ExitCode {
-        let mut cmd: Box<dyn ForAll> = match self {
-            Command::List(c) => Box::new(c),
-            Command::Clean(c) => Box::new(c),
-            Command::Cloc(c) => Box::new(c),
-            Command::Gc(c) => Box::new(c),
-            Command::PreUpdate(c) => Box::new(c),
-            Command::Pull(c) => Box::new(c),
-            Command::Push(c) => Box::new(c),
-            Command::Rsclean(c) => Box::new(c),
-            Command::Run(c) => match c.into_forall() {
-                Ok(cmd) => cmd,
-                Err(e) => {
-                    logerror(e.context("Failed to initialize command"));
-                    return ExitCode::FAILURE;
-                }
-            },
-            Command::RunPr(c) => match c.into_forall() {
-                Ok(cmd) => cmd,
-                Err(e) => {
-                    logerror(e.context("Failed to initialize command"));
-                    return ExitCode::FAILURE;
-                }
-            },
+    pub(crate) fn run(self, opts: Options, discovery: Discovery) -> ExitCode {
+        let Discovery { projects, problems } = discovery;
+        let cmd: anyhow::Result<Box<dyn ForAll>> = match self {
+            Command::List(c) => Ok(Box::new(c)),
+            Command::BumpDep(c) => c.into_forall(),
+            Command::CiStatus(c) => c.into_forall(),
+            Command::Clean(c) => Ok(Box::new(c)),
+            Command::Cloc(c) => Ok(Box::new(c)),
+            Command::Doctor(c) => return c.run(&problems),
+            Command::Gc(c) => Ok(Box::new(c)),
+            Command::GhAudit(c) => c.into_forall(),
+            Command::GhSummary(c) => c.into_forall(),
+            Command::Graph(c) => Ok(c.into_forall()),
+            Command::Labels(c) => c.into_forall(),
+            Command::Outdated(c) => Ok(c.into_forall()),
+            Command::PreUpdate(c) => Ok(Box::new(c)),
+            Command::PrClose(c) => c.into_forall(),
+            Command::PrMerge(c) => c.into_forall(),
+            Command::PrStatus(c) => c.into_forall(),
+            Command::PruneBranches(c) => c.into_forall(),
+            Command::Pull(c) => Ok(Box::new(c)),
+            Command::Push(c) => Ok(Box::new(c)),
+            Command::Query(c) => c.into_forall(),
+            Command::Release(c) => c.into_forall(),
+            Command::Rsclean(c) => Ok(Box::new(c)),
+            Command::Run(c) => c.into_forall(),
+            Command::RunPr(c) => c.into_forall(),
+            Command::Versions(c) => Ok(c.into_forall()),
         };
+        let mut cmd = match cmd {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                logerror(e.context("Failed to initialize command"));
+                return ExitCode::FAILURE;
+            }
+        };
+        let mut bad_dirs = Vec::new();
+        for prob in problems {
+            match prob.kind {
+                ProblemKind::Invalid(e) => {
+                    let e = e.context(format!(
+                        "Failed to load project at {}",
+                        prob.dirpath.display()
+                    ));
+                    if opts.keep_going {
+                        logerror(e);
+                        bad_dirs.push(prob.dirpath);
+                    } else {
+                        logwarning(&e);
+                    }
+                }
+                ProblemKind::Unrecognized => debug!(
+                    "{} does not contain a recognized project; skipping",
+                    prob.dirpath.display()
+                ),
+            }
+        }
         let mut failures = Vec::new();
+        let mut aborted = false;
         for p in projects {
             if let Err(e) = cmd.run(&p) {
                 logerror(e);
                 if opts.keep_going {
                     failures.push(p);
                 } else {
-                    return ExitCode::FAILURE;
+                    aborted = true;
+                    break;
                 }
             }
         }
-        if failures.is_empty() {
+        // Output the aggregated results for the projects that were operated
+        // on even if a failure cut the run short
+        if let Err(e) = cmd.finish() {
+            logerror(e);
+            return ExitCode::FAILURE;
+        }
+        if aborted {
+            return ExitCode::FAILURE;
+        }
+        if failures.is_empty() && bad_dirs.is_empty() {
             ExitCode::SUCCESS
         } else {
             anstream::println!(
                 "\n{bold}Failures:{bold:#}",
                 bold = anstyle::Style::new().bold()
             );
+            for dirpath in bad_dirs {
+                println!("{}", dirpath.display());
+            }
             for p in failures {
                 println!("{}", p.name());
             }
@@ -0,0 +1,222 @@
+use super::ForAll;
+use crate::pep440;
+use crate::project::{Language, Project};
+use crate::registry::{CargoIndex, SimpleIndex};
+use crate::table::Table;
+use anyhow::Context;
+use cargo_metadata::semver;
+use clap::Args;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// List dependencies whose requirements exclude their newest versions
+///
+/// The newest version of each dependency is looked up in a local copy of its
+/// package index, so no network access is needed.  For Rust projects, the
+/// requirements on crates.io packages in the `Cargo.toml` files of the project
+/// and its workspace members are checked against `--cargo-index`; for Python
+/// projects, the PEP 508 requirements in `pyproject.toml` and `setup.cfg` are
+/// checked against `--pypi-index`.  Pre-release and yanked versions are not
+/// considered.
+///
+/// The results are aggregated across all projects, showing for each outdated
+/// dependency its newest version, the number of projects whose requirements
+/// exclude that version, and those requirements.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Outdated {
+    /// Directory containing a local copy of the crates.io index, used for
+    /// Rust projects
+    #[arg(long, value_name = "DIRPATH", required_unless_present = "pypi_index")]
+    cargo_index: Option<PathBuf>,
+
+    /// Directory containing a local PEP 503 "simple" repository, used for
+    /// Python projects
+    #[arg(long, value_name = "DIRPATH")]
+    pypi_index: Option<PathBuf>,
+
+    /// Output JSON
+    #[arg(short = 'J', long)]
+    json: bool,
+}
+
+impl Outdated {
+    pub(super) fn into_forall(self) -> Box<dyn ForAll> {
+        Box::new(OutdatedForAll {
+            cargo_index: self.cargo_index.map(CargoIndex::new),
+            pypi_index: self.pypi_index.map(SimpleIndex::new),
+            json: self.json,
+            cargo_latest: HashMap::new(),
+            pypi_latest: HashMap::new(),
+            outdated: HashMap::new(),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct OutdatedForAll {
+    cargo_index: Option<CargoIndex>,
+    pypi_index: Option<SimpleIndex>,
+    json: bool,
+    /// Cache of the newest versions of crates
+    cargo_latest: HashMap<String, Option<semver::Version>>,
+    /// Cache of the newest versions of Python packages
+    pypi_latest: HashMap<String, Option<pep440::Version>>,
+    /// The outdated requirements found so far, keyed by language and package
+    /// name
+    outdated: HashMap<(Language, String), OutdatedDependency>,
+}
+
+impl OutdatedForAll {
+    /// If the requirement `req` on the package `name` excludes the newest
+    /// version of the package, return that version
+    fn check(&mut self, lang: Language, name: &str, req: &str) -> anyhow::Result<Option<String>> {
+        match lang {
+            Language::Rust => {
+                let Some(ref index) = self.cargo_index else {
+                    return Ok(None);
+                };
+                let latest = match self.cargo_latest.get(name) {
+                    Some(latest) => latest,
+                    None => self
+                        .cargo_latest
+                        .entry(name.to_owned())
+                        .or_insert(index.latest_version(name)?),
+                };
+                let Some(latest) = latest else {
+                    debug!("Crate {name} not found in Cargo index");
+                    return Ok(None);
+                };
+                let req = req
+                    .parse::<semver::VersionReq>()
+                    .with_context(|| format!("invalid version requirement for {name}: {req:?}"))?;
+                Ok((!req.matches(latest)).then(|| latest.to_string()))
+            }
+            Language::Python => {
+                let Some(ref index) = self.pypi_index else {
+                    return Ok(None);
+                };
+                let latest = match self.pypi_latest.get(name) {
+                    Some(latest) => latest,
+                    None => self
+                        .pypi_latest
+                        .entry(name.to_owned())
+                        .or_insert(index.latest_version(name)?),
+                };
+                let Some(latest) = latest else {
+                    debug!("Package {name} not found in Python package index");
+                    return Ok(None);
+                };
+                let specs = req
+                    .parse::<pep440::SpecifierSet>()
+                    .with_context(|| format!("invalid version specifier for {name}"))?;
+                Ok((!specs.contains(latest)).then(|| latest.to_string()))
+            }
+        }
+    }
+}
+
+impl ForAll for OutdatedForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let indexed = match p.language() {
+            Language::Rust => self.cargo_index.is_some(),
+            Language::Python => self.pypi_index.is_some(),
+        };
+        if !indexed {
+            debug!(
+                "No package index given for {} projects; skipping {}",
+                p.language(),
+                p.name()
+            );
+            return Ok(());
+        }
+        for req in p.requirements()? {
+            if let Some(latest) = self.check(p.language(), &req.name, &req.version)? {
+                self.outdated
+                    .entry((p.language(), req.name))
+                    .or_insert_with(|| OutdatedDependency {
+                        latest,
+                        projects: Vec::new(),
+                    })
+                    .projects
+                    .push(ProjectRequirement {
+                        project: p.name().to_owned(),
+                        requirement: req.version,
+                    });
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        let mut outdated = self.outdated.drain().collect::<Vec<_>>();
+        outdated.sort_by(|((lang1, name1), dep1), ((lang2, name2), dep2)| {
+            (Reverse(dep1.projects.len()), name1, lang1).cmp(&(
+                Reverse(dep2.projects.len()),
+                name2,
+                lang2,
+            ))
+        });
+        let mut table = Table::new([
+            "DEPENDENCY",
+            "LANGUAGE",
+            "LATEST",
+            "PROJECTS",
+            "REQUIREMENTS",
+        ]);
+        for ((language, dependency), dep) in outdated {
+            if self.json {
+                let report = OutdatedReport {
+                    dependency: &dependency,
+                    language,
+                    latest: &dep.latest,
+                    projects: &dep.projects,
+                };
+                println!(
+                    "{}",
+                    serde_json::to_string(&report).expect("JSONification should not fail")
+                );
+            } else {
+                let mut reqs = dep
+                    .projects
+                    .iter()
+                    .map(|pr| pr.requirement.as_str())
+                    .collect::<Vec<_>>();
+                reqs.sort_unstable();
+                reqs.dedup();
+                table.push(vec![
+                    dependency,
+                    language.to_string(),
+                    dep.latest,
+                    dep.projects.len().to_string(),
+                    reqs.join(", "),
+                ]);
+            }
+        }
+        if !table.is_empty() {
+            print!("{table}");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct OutdatedDependency {
+    latest: String,
+    projects: Vec<ProjectRequirement>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct ProjectRequirement {
+    project: String,
+    requirement: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct OutdatedReport<'a> {
+    dependency: &'a str,
+    language: Language,
+    latest: &'a str,
+    projects: &'a [ProjectRequirement],
+}
@@ -0,0 +1,53 @@
+use super::ForAll;
+use super::prs::{PrSelection, delete_pr_branch};
+use crate::github::GitHub;
+use crate::logging::logproject;
+use crate::project::Project;
+use clap::Args;
+
+/// Close selected open pull requests without merging and delete their
+/// branches
+///
+/// Only projects that have GitHub remotes are considered.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct PrClose {
+    #[command(flatten)]
+    selection: PrSelection,
+}
+
+impl PrClose {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let github = GitHub::authed()?;
+        Ok(Box::new(PrCloseForAll {
+            github,
+            selection: self.selection,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PrCloseForAll {
+    github: GitHub,
+    selection: PrSelection,
+}
+
+impl ForAll for PrCloseForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let Some(ghrepo) = p.ghrepo() else {
+            debug!("{} does not have a GitHub repository; skipping", p.name());
+            return Ok(());
+        };
+        let prs = self.selection.select(&self.github, ghrepo)?;
+        if prs.is_empty() {
+            debug!("{}: no matching pull requests", p.name());
+            return Ok(());
+        }
+        logproject(p);
+        for pr in prs {
+            self.github.close_pull_request(ghrepo, pr.number)?;
+            info!("Closed {ghrepo}#{}", pr.number);
+            delete_pr_branch(&self.github, p, ghrepo, &pr)?;
+        }
+        Ok(())
+    }
+}
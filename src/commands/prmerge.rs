@@ -0,0 +1,80 @@
+use super::ForAll;
+use super::prs::{PrSelection, delete_pr_branch};
+use crate::github::{CiStatus, GitHub, MergeMethod};
+use crate::logging::logproject;
+use crate::project::Project;
+use clap::Args;
+
+/// Merge selected open pull requests whose checks have passed and delete
+/// their branches
+///
+/// Only projects that have GitHub remotes are considered.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct PrMerge {
+    /// Merge pull requests even if their checks have not passed
+    #[arg(long)]
+    force: bool,
+
+    /// Method to use for merging
+    #[arg(long, value_enum, default_value = "merge", value_name = "METHOD")]
+    method: MergeMethod,
+
+    #[command(flatten)]
+    selection: PrSelection,
+}
+
+impl PrMerge {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let github = GitHub::authed()?;
+        Ok(Box::new(PrMergeForAll {
+            github,
+            force: self.force,
+            method: self.method,
+            selection: self.selection,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PrMergeForAll {
+    github: GitHub,
+    force: bool,
+    method: MergeMethod,
+    selection: PrSelection,
+}
+
+impl ForAll for PrMergeForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let Some(ghrepo) = p.ghrepo() else {
+            debug!("{} does not have a GitHub repository; skipping", p.name());
+            return Ok(());
+        };
+        let prs = self.selection.select(&self.github, ghrepo)?;
+        if prs.is_empty() {
+            debug!("{}: no matching pull requests", p.name());
+            return Ok(());
+        }
+        logproject(p);
+        for pr in prs {
+            if pr.draft {
+                info!("{ghrepo}#{} is a draft; not merging", pr.number);
+                continue;
+            }
+            if !self.force {
+                let checks = self.github.get_ci_status(ghrepo, &pr.head.sha)?;
+                if checks != CiStatus::Success {
+                    info!(
+                        "Checks for {ghrepo}#{} have status {checks}; not merging",
+                        pr.number
+                    );
+                    continue;
+                }
+            }
+            self.github
+                .merge_pull_request(ghrepo, pr.number, self.method)?;
+            info!("Merged {ghrepo}#{}", pr.number);
+            delete_pr_branch(&self.github, p, ghrepo, &pr)?;
+        }
+        Ok(())
+    }
+}
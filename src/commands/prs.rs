@@ -0,0 +1,147 @@
+use crate::github::{GitHub, PullRequest};
+use crate::project::Project;
+use crate::util::glob_match;
+use clap::Args;
+use ghrepo::GHRepo;
+
+static DEFAULT_BRANCH_PATTERN: &str = "forall-runpr-*";
+
+/// Options for selecting open pull requests in each project's repository
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct PrSelection {
+    /// Select pull requests whose head branch matches the given glob pattern.
+    ///
+    /// Defaults to `forall-runpr-*` if `--label` is not given.
+    #[arg(short, long, value_name = "PATTERN")]
+    branch: Option<String>,
+
+    /// Select pull requests with the given label
+    #[arg(short, long, value_name = "NAME")]
+    label: Option<String>,
+}
+
+impl PrSelection {
+    fn branch_pattern(&self) -> Option<&str> {
+        match (&self.branch, &self.label) {
+            (Some(pattern), _) => Some(pattern),
+            (None, Some(_)) => None,
+            (None, None) => Some(DEFAULT_BRANCH_PATTERN),
+        }
+    }
+
+    fn matches(&self, pr: &PullRequest) -> bool {
+        if let Some(pattern) = self.branch_pattern()
+            && !glob_match(pattern, &pr.head.gitref)
+        {
+            return false;
+        }
+        if let Some(ref label) = self.label
+            && !pr
+                .labels
+                .iter()
+                .any(|lbl| lbl.name.eq_ignore_ascii_case(label))
+        {
+            return false;
+        }
+        true
+    }
+
+    /// Return the selected open pull requests for the given repository
+    pub(super) fn select(
+        &self,
+        github: &GitHub,
+        ghrepo: &GHRepo,
+    ) -> anyhow::Result<Vec<PullRequest>> {
+        let mut prs = github.list_open_pull_requests(ghrepo)?;
+        prs.retain(|pr| self.matches(pr));
+        Ok(prs)
+    }
+}
+
+/// Delete the head branch of `pr` from both the remote repository and the
+/// local checkout.  If the branch is currently checked out locally, the
+/// default branch is checked out first.
+///
+/// The remote branch is only deleted if it belongs to `ghrepo` rather than to
+/// a fork.  The local branch is deleted even if it is unmerged as long as it
+/// points to the PR's head commit (so that nothing is lost); otherwise, it is
+/// only deleted if it has been merged.
+pub(super) fn delete_pr_branch(
+    github: &GitHub,
+    p: &Project,
+    ghrepo: &GHRepo,
+    pr: &PullRequest,
+) -> anyhow::Result<()> {
+    let branch = &pr.head.gitref;
+    if pr
+        .head
+        .repo
+        .as_ref()
+        .is_some_and(|r| r.full_name.eq_ignore_ascii_case(ghrepo.as_ref()))
+    {
+        github.delete_branch(ghrepo, branch)?;
+        info!("Deleted branch {branch:?} from {ghrepo}");
+    }
+    let force = p
+        .branch_commit(branch)?
+        .is_some_and(|oid| oid == pr.head.sha);
+    p.delete_local_branch(branch, force)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::{LabelInfo, PullRequestHead};
+    use rstest::rstest;
+
+    fn pr(branch: &str, labels: &[&str]) -> PullRequest {
+        PullRequest {
+            url: String::new(),
+            html_url: String::new(),
+            number: 1,
+            state: String::from("open"),
+            draft: false,
+            created_at: String::from("2025-01-01T00:00:00Z"),
+            head: PullRequestHead {
+                gitref: branch.to_owned(),
+                sha: String::new(),
+                repo: None,
+            },
+            labels: labels
+                .iter()
+                .map(|&name| LabelInfo {
+                    name: name.to_owned(),
+                    color: String::from("ededed"),
+                    description: None,
+                })
+                .collect(),
+            merged: false,
+            mergeable: None,
+            mergeable_state: None,
+        }
+    }
+
+    #[rstest]
+    #[case(None, None, "forall-runpr-20250101000000", &[], true)]
+    #[case(None, None, "feature", &[], false)]
+    #[case(Some("bump-*"), None, "bump-serde", &[], true)]
+    #[case(Some("bump-*"), None, "forall-runpr-20250101000000", &[], false)]
+    #[case(None, Some("dependencies"), "feature", &["Dependencies"], true)]
+    #[case(None, Some("dependencies"), "feature", &["bug"], false)]
+    #[case(Some("bump-*"), Some("dependencies"), "bump-serde", &["dependencies"], true)]
+    #[case(Some("bump-*"), Some("dependencies"), "bump-serde", &[], false)]
+    fn test_matches(
+        #[case] branch: Option<&str>,
+        #[case] label: Option<&str>,
+        #[case] head: &str,
+        #[case] labels: &[&str],
+        #[case] m: bool,
+    ) {
+        let sel = PrSelection {
+            branch: branch.map(ToOwned::to_owned),
+            label: label.map(ToOwned::to_owned),
+        };
+        assert_eq!(sel.matches(&pr(head, labels)), m);
+    }
+}
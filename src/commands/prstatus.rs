@@ -0,0 +1,122 @@
+use super::ForAll;
+use crate::github::{CiStatus, GitHub, ReviewStatus};
+use crate::project::Project;
+use crate::sweep::{SweepLog, SweepRecord};
+use crate::table::Table;
+use clap::Args;
+use ghrepo::GHRepo;
+use serde::Serialize;
+
+/// Show the status of the pull requests created by a `run-pr` sweep
+///
+/// Only pull requests for the selected projects are shown.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct PrStatus {
+    /// Output JSON
+    #[arg(short = 'J', long)]
+    json: bool,
+
+    /// Show the pull requests created with the given branch name.  Defaults
+    /// to the branch of the most recent sweep.
+    #[arg(short, long, value_name = "BRANCH")]
+    sweep: Option<String>,
+}
+
+impl PrStatus {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let github = GitHub::authed()?;
+        let sweeplog = SweepLog::default_location()?;
+        let records = sweeplog.read_sweep(self.sweep.as_deref())?;
+        if records.is_empty() {
+            info!("No pull requests recorded in {}", sweeplog.path().display());
+        }
+        Ok(Box::new(PrStatusForAll {
+            github,
+            records,
+            json: self.json,
+            table: Table::new(["PROJECT", "PR", "STATE", "CHECKS", "REVIEW", "MERGEABLE"]),
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PrStatusForAll {
+    github: GitHub,
+    records: Vec<SweepRecord>,
+    json: bool,
+    table: Table,
+}
+
+impl ForAll for PrStatusForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let Some(ghrepo) = p.ghrepo() else {
+            return Ok(());
+        };
+        for rec in self.records.iter().filter(|r| &r.repo == ghrepo) {
+            let pr = self.github.get_pull_request(ghrepo, rec.number)?;
+            let checks = self.github.get_ci_status(ghrepo, &pr.head.sha)?;
+            let review = self.github.get_review_status(ghrepo, rec.number)?;
+            let status = PrStatusDetails {
+                project: p.name(),
+                repo: ghrepo,
+                branch: &rec.branch,
+                number: pr.number,
+                html_url: &pr.html_url,
+                state: pr.display_state(),
+                checks,
+                review,
+                mergeable: pr.mergeable,
+                mergeable_state: pr.mergeable_state.as_deref(),
+            };
+            if self.json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&status).expect("JSONification should not fail")
+                );
+            } else {
+                self.table.push(status.to_row());
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if !self.json && !self.table.is_empty() {
+            print!("{}", self.table);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct PrStatusDetails<'a> {
+    project: &'a str,
+    repo: &'a GHRepo,
+    branch: &'a str,
+    number: u64,
+    html_url: &'a str,
+    state: &'a str,
+    checks: CiStatus,
+    review: ReviewStatus,
+    mergeable: Option<bool>,
+    mergeable_state: Option<&'a str>,
+}
+
+impl PrStatusDetails<'_> {
+    fn to_row(&self) -> Vec<String> {
+        let mergeable = match (self.mergeable, self.mergeable_state) {
+            (_, Some(st)) if st != "unknown" => st.to_owned(),
+            (Some(true), _) => String::from("yes"),
+            (Some(false), _) => String::from("no"),
+            (None, _) => String::from("unknown"),
+        };
+        vec![
+            self.project.to_owned(),
+            format!("{}#{}", self.repo, self.number),
+            self.state.to_owned(),
+            self.checks.to_string(),
+            self.review.to_string(),
+            mergeable,
+        ]
+    }
+}
@@ -0,0 +1,97 @@
+use super::ForAll;
+use crate::github::GitHub;
+use crate::logging::logproject;
+use crate::project::Project;
+use crate::util::glob_match;
+use clap::Args;
+use std::collections::HashSet;
+
+/// Delete local and remote branches left over from `run-pr`
+///
+/// Branches that are the head of an open pull request are not deleted.
+/// Remote branches are only deleted for projects that have GitHub remotes.
+/// Local branches that have not been merged are only deleted if `--force` is
+/// given.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct PruneBranches {
+    /// Delete branches whose names match the given glob pattern
+    #[arg(short, long, default_value = "forall-runpr-*", value_name = "PATTERN")]
+    branch: String,
+
+    /// Show which branches would be deleted without deleting them
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+
+    /// Delete local branches even if they have not been merged
+    #[arg(short, long)]
+    force: bool,
+}
+
+impl PruneBranches {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let github = GitHub::authed()?;
+        Ok(Box::new(PruneBranchesForAll {
+            github,
+            branch: self.branch,
+            dry_run: self.dry_run,
+            force: self.force,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PruneBranchesForAll {
+    github: GitHub,
+    branch: String,
+    dry_run: bool,
+    force: bool,
+}
+
+impl ForAll for PruneBranchesForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let mut remote = Vec::new();
+        let mut in_use = HashSet::new();
+        if let Some(ghrepo) = p.ghrepo() {
+            in_use.extend(
+                self.github
+                    .list_open_pull_requests(ghrepo)?
+                    .into_iter()
+                    .map(|pr| pr.head.gitref),
+            );
+            remote.extend(
+                self.github
+                    .get_branch_names(ghrepo)?
+                    .into_iter()
+                    .filter(|b| glob_match(&self.branch, b) && !in_use.contains(b)),
+            );
+        }
+        let local = p
+            .local_branches()?
+            .into_iter()
+            .filter(|b| glob_match(&self.branch, b) && !in_use.contains(b))
+            .collect::<Vec<_>>();
+        if remote.is_empty() && local.is_empty() {
+            debug!("{}: no matching branches", p.name());
+            return Ok(());
+        }
+        logproject(p);
+        if let Some(ghrepo) = p.ghrepo() {
+            for b in remote {
+                if self.dry_run {
+                    info!("Would delete branch {b:?} from {ghrepo}");
+                } else {
+                    self.github.delete_branch(ghrepo, &b)?;
+                    info!("Deleted branch {b:?} from {ghrepo}");
+                }
+            }
+        }
+        for b in local {
+            if self.dry_run {
+                info!("Would delete local branch {b:?}");
+            } else {
+                p.delete_local_branch(&b, self.force)?;
+            }
+        }
+        Ok(())
+    }
+}
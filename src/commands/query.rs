@@ -0,0 +1,175 @@
+use super::ForAll;
+use crate::cmd::CommandError;
+use crate::project::Project;
+use crate::util::{RunOpts, Runner};
+use clap::Args;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::fmt::Write;
+
+/// Run a command on each project and group the projects by output
+///
+/// The command's stdout is captured for each project, and projects that
+/// produced identical output are grouped together.  Projects for which the
+/// command fails are grouped separately by exit status and output.  The groups
+/// are shown in decreasing order of size; the projects in each group other
+/// than the largest are listed by name, making it easy to spot the odd ones
+/// out.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Query {
+    /// Output JSON
+    #[arg(short = 'J', long)]
+    json: bool,
+
+    #[command(flatten)]
+    run_opts: RunOpts,
+}
+
+impl Query {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        Ok(Box::new(QueryForAll {
+            runner: Runner::try_from(self.run_opts)?,
+            json: self.json,
+            groups: Vec::new(),
+        }))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct QueryForAll {
+    runner: Runner,
+    json: bool,
+    /// The distinct outputs seen so far, in order of first appearance
+    groups: Vec<OutputGroup>,
+}
+
+impl QueryForAll {
+    fn add(&mut self, project: &str, output: String, failure: Option<String>) {
+        let project = project.to_owned();
+        if let Some(group) = self
+            .groups
+            .iter_mut()
+            .find(|g| g.output == output && g.failure == failure)
+        {
+            group.projects.push(project);
+        } else {
+            self.groups.push(OutputGroup {
+                output,
+                failure,
+                projects: vec![project],
+            });
+        }
+    }
+
+    /// Sort the groups by decreasing size, with ties broken by order of
+    /// first appearance
+    fn sort_groups(&mut self) {
+        self.groups.sort_by_key(|g| Reverse(g.projects.len()));
+    }
+}
+
+impl ForAll for QueryForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let (output, failure) = match self.runner.check_output(p) {
+            Ok(output) => (output, None),
+            Err(CommandError::Exit { rc, stdout, .. }) => {
+                (stdout.unwrap_or_default(), Some(rc.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        self.add(p.name(), output, failure);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        self.sort_groups();
+        if self.json {
+            for group in &self.groups {
+                println!(
+                    "{}",
+                    serde_json::to_string(group).expect("JSONification should not fail")
+                );
+            }
+        } else {
+            print!("{}", show_groups(&self.groups));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct OutputGroup {
+    output: String,
+    /// The exit status with which the command failed, or `None` if it
+    /// succeeded
+    failure: Option<String>,
+    projects: Vec<String>,
+}
+
+/// Render the (already sorted) groups as text.  Each group is shown as a
+/// header line giving the number of projects (followed by their names for all
+/// groups but the first, and by the exit status for failures) and then the
+/// output, indented.
+fn show_groups(groups: &[OutputGroup]) -> String {
+    let mut s = String::new();
+    for (i, group) in groups.iter().enumerate() {
+        let qty = group.projects.len();
+        let _ = write!(s, "{qty} project{}", if qty == 1 { "" } else { "s" });
+        if i > 0 {
+            let _ = write!(s, " [{}]", group.projects.join(", "));
+        }
+        if let Some(ref failure) = group.failure {
+            let _ = write!(s, " (failed: {failure})");
+        }
+        s.push_str(":\n");
+        if group.output.trim().is_empty() {
+            s.push_str("    (no output)\n");
+        } else {
+            for line in group.output.trim_end().lines() {
+                let _ = writeln!(s, "    {line}");
+            }
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_group_outputs() {
+        let opts = RunOpts {
+            script: false,
+            shell: false,
+            command: vec![OsString::from("true")],
+        };
+        let mut qfa = QueryForAll {
+            runner: Runner::try_from(opts).unwrap(),
+            json: false,
+            groups: Vec::new(),
+        };
+        qfa.add("foo", "user@example.com\n".into(), None);
+        qfa.add("bar", "other@example.org\n".into(), None);
+        qfa.add("baz", "user@example.com\n".into(), None);
+        qfa.add("quux", String::new(), None);
+        qfa.add("glarch", "user@example.com\n".into(), None);
+        qfa.add("cleesh", "other@example.org\n".into(), None);
+        qfa.add("gnusto", String::new(), Some("exit status: 1".into()));
+        qfa.sort_groups();
+        assert_eq!(
+            show_groups(&qfa.groups),
+            concat!(
+                "3 projects:\n",
+                "    user@example.com\n",
+                "2 projects [bar, cleesh]:\n",
+                "    other@example.org\n",
+                "1 project [quux]:\n",
+                "    (no output)\n",
+                "1 project [gnusto] (failed: exit status: 1):\n",
+                "    (no output)\n",
+            )
+        );
+    }
+}
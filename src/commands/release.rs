@@ -0,0 +1,142 @@
+use super::ForAll;
+use crate::changelog::Changelog;
+use crate::github::{CreateRelease, GitHub};
+use crate::logging::logproject;
+use crate::project::Project;
+use clap::Args;
+use fs_err::PathExt;
+use std::borrow::Cow;
+
+/// Tag each project's current version and create a GitHub release for it
+///
+/// For each project, the version declared in `Cargo.toml` or
+/// `pyproject.toml` is read, and, if there is not already a `v{version}` tag
+/// either locally or on `origin`, an annotated tag is created for `HEAD` and
+/// pushed to `origin`.  If there is not already a GitHub release for the tag,
+/// one is then created.  If the project has a `CHANGELOG.md`, the section for
+/// the version is used as the release notes.
+///
+/// Projects that need to be tagged must be on their default branch and must
+/// not have any uncommitted changes.
+///
+/// Only projects that have GitHub remotes are considered.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Release {
+    /// Create the GitHub releases as drafts
+    #[arg(long)]
+    draft: bool,
+
+    /// Show what would be released without doing anything
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+}
+
+impl Release {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let github = GitHub::authed()?;
+        Ok(Box::new(ReleaseForAll {
+            github,
+            draft: self.draft,
+            dry_run: self.dry_run,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ReleaseForAll {
+    github: GitHub,
+    draft: bool,
+    dry_run: bool,
+}
+
+impl ForAll for ReleaseForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let Some(ghrepo) = p.ghrepo() else {
+            debug!("{} does not have a GitHub repository; skipping", p.name());
+            return Ok(());
+        };
+        let Some(version) = p.version() else {
+            anyhow::bail!("Could not determine version of {}", p.name());
+        };
+        let tag = format!("v{version}");
+        if self.github.get_release_for_tag(ghrepo, &tag)?.is_some() {
+            debug!("{} has already been released as {tag}; skipping", p.name());
+            return Ok(());
+        }
+        let local_tag = !p.readcmd("git", ["tag", "--list", &tag])?.is_empty();
+        let remote_tag = !p
+            .readcmd(
+                "git",
+                ["ls-remote", "--tags", "origin", &format!("refs/tags/{tag}")],
+            )?
+            .is_empty();
+        if !local_tag && !remote_tag {
+            let defbranch = p.default_branch()?;
+            if p.current_branch()?.as_ref() != Some(&defbranch) {
+                anyhow::bail!(
+                    "{} is not on its default branch ({defbranch}); not tagging",
+                    p.name()
+                );
+            }
+            if p.is_dirty()? {
+                anyhow::bail!("{} has uncommitted changes; not tagging", p.name());
+            }
+        }
+        let notes = release_notes(p, version)?;
+        logproject(p);
+        if self.dry_run {
+            if local_tag || remote_tag {
+                info!("Would create a GitHub release for existing tag {tag} in {ghrepo}");
+            } else {
+                info!("Would tag {tag} and create a GitHub release for {ghrepo}");
+            }
+            return Ok(());
+        }
+        if !local_tag && !remote_tag {
+            p.runcmd("git")
+                .args(["tag", "-a", "-m"])
+                .arg(format!("Version {version}"))
+                .arg(&tag)
+                .run()?;
+        }
+        if !remote_tag {
+            p.runcmd("git").args(["push", "origin"]).arg(&tag).run()?;
+        }
+        let release = self.github.create_release(
+            ghrepo,
+            CreateRelease {
+                tag_name: Cow::from(&tag),
+                name: Cow::from(&tag),
+                body: notes.map(Cow::from),
+                draft: self.draft,
+                // Versions with pre-release identifiers (e.g., "1.0.0-rc.1"
+                // or "1.0.0rc1") contain letters
+                prerelease: version.contains(|c: char| c.is_ascii_alphabetic()),
+            },
+        )?;
+        println!("{}", release.html_url);
+        Ok(())
+    }
+}
+
+/// Extract the release notes for `version` from the project's
+/// `CHANGELOG.md`.  Returns `None` if the project does not have a changelog.
+/// It is an error for the changelog to lack a section for the version or for
+/// that section to still be marked as in development.
+fn release_notes(p: &Project, version: &str) -> anyhow::Result<Option<String>> {
+    let path = p.dirpath().join("CHANGELOG.md");
+    if !path.fs_err_try_exists()? {
+        return Ok(None);
+    }
+    let changelog = Changelog::parse(&fs_err::read_to_string(&path)?);
+    let Some(section) = changelog.section(version) else {
+        anyhow::bail!("CHANGELOG.md for {} has no section for {version}", p.name());
+    };
+    if section.in_development() {
+        anyhow::bail!(
+            "CHANGELOG.md section for {} {version} is still marked as in development",
+            p.name()
+        );
+    }
+    Ok(Some(section.body.clone()))
+}
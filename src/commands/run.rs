@@ -1,29 +1,64 @@
-use super::ForAll;
-use crate::logging::logproject;
+use super::{ForAll, restore_after};
+use crate::cmd::CommandError;
+use crate::logging::{logerror, logmember, logproject};
 use crate::project::Project;
 use crate::util::{RunOpts, Runner};
+use anyhow::Context;
 use clap::Args;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// The name of the file in the `--log-dir` directory listing each project's
+/// log file and exit status
+static LOG_INDEX_FILE: &str = "index.tsv";
 
 /// Run a command on each project.
 ///
 /// The command is run with the current working directory set to each
 /// respective project's directory.
 #[derive(Args, Clone, Debug, Eq, PartialEq)]
+#[expect(
+    clippy::struct_field_names,
+    reason = "`run_opts` matches the field name used by `RunPr`"
+)]
 pub(crate) struct Run {
     #[command(flatten)]
     pub(crate) run_opts: RunOpts,
 
-    /// Stash any uncommitted changes before running the command
+    /// Stash any uncommitted changes before running the command.
+    ///
+    /// Afterwards, the previously checked-out branch is checked out and the
+    /// stash is popped unless `--no-restore` is given.
     #[arg(short, long)]
     pub(crate) stash: bool,
+
+    /// With `--stash`, don't check out the previously checked-out branch and
+    /// pop the stash afterwards
+    #[arg(long, requires = "stash")]
+    pub(crate) no_restore: bool,
+
+    /// For Rust workspaces, run the command once in each member package's
+    /// directory instead of once in the workspace root
+    #[arg(long)]
+    pub(crate) per_member: bool,
+
+    /// Write each project's combined stdout and stderr, with timestamps, to
+    /// `DIR/<name>.log`, and write a list of the log files and the commands'
+    /// exit statuses to `DIR/index.tsv`
+    #[arg(long, value_name = "DIR")]
+    pub(crate) log_dir: Option<PathBuf>,
 }
 
 impl Run {
     pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
         let runner = Runner::try_from(self.run_opts)?;
+        let log_dir = self.log_dir.map(LogDir::create).transpose()?;
         Ok(Box::new(RunForAll {
             runner,
             stash: self.stash,
+            no_restore: self.no_restore,
+            per_member: self.per_member,
+            log_dir,
         }))
     }
 }
@@ -32,15 +67,113 @@ impl Run {
 struct RunForAll {
     runner: Runner,
     stash: bool,
+    no_restore: bool,
+    per_member: bool,
+    log_dir: Option<LogDir>,
+}
+
+impl RunForAll {
+    fn run_command(&self, p: &Project) -> anyhow::Result<()> {
+        let members = if self.per_member {
+            p.members()?
+        } else {
+            Vec::new()
+        };
+        let mut log = match self.log_dir {
+            Some(ref log_dir) => Some(log_dir.open_log(p)?),
+            None => None,
+        };
+        if members.is_empty() {
+            self.run_in(p, log.as_mut())?;
+        } else {
+            for m in members {
+                logmember(p, &m);
+                self.run_in(&p.with_dirpath(p.dirpath().join(&m.dirpath)), log.as_mut())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn run_project(&self, p: &Project) -> anyhow::Result<()> {
+        if self.stash && !self.no_restore {
+            let checkpoint = p.checkpoint()?;
+            let r = self.run_command(p);
+            restore_after(p, &checkpoint, r)
+        } else {
+            if self.stash {
+                p.stash()?;
+            }
+            self.run_command(p)
+        }
+    }
+
+    fn run_in<W: Write>(&self, p: &Project, log: Option<&mut W>) -> Result<(), CommandError> {
+        match log {
+            Some(log) => self.runner.run_logged(p, log),
+            None => self.runner.run(p),
+        }
+    }
 }
 
 impl ForAll for RunForAll {
     fn run(&mut self, p: &Project) -> anyhow::Result<()> {
         logproject(p);
-        if self.stash {
-            p.stash()?;
+        let r = self.run_project(p);
+        if let Some(ref log_dir) = self.log_dir {
+            // Record the project even if the run failed so that the index is
+            // complete when `--keep-going` isn't in effect
+            if let Err(e) = log_dir.record(p, r.as_ref().err()) {
+                if r.is_ok() {
+                    return Err(e);
+                }
+                logerror(e);
+            }
         }
-        self.runner.run(p)?;
+        r
+    }
+}
+
+/// A directory to which `run --log-dir` writes logs
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct LogDir {
+    dirpath: PathBuf,
+}
+
+impl LogDir {
+    /// Create the directory if it does not exist and start a new index file
+    /// in it
+    fn create(dirpath: PathBuf) -> anyhow::Result<LogDir> {
+        fs_err::create_dir_all(&dirpath)?;
+        fs_err::write(dirpath.join(LOG_INDEX_FILE), "project\tstatus\tlog\n")?;
+        Ok(LogDir { dirpath })
+    }
+
+    fn log_name(p: &Project) -> String {
+        format!("{}.log", p.name())
+    }
+
+    /// Create (or truncate) the log file for the given project
+    fn open_log(&self, p: &Project) -> anyhow::Result<BufWriter<fs_err::File>> {
+        let file = fs_err::File::create(self.dirpath.join(LogDir::log_name(p)))?;
+        Ok(BufWriter::new(file))
+    }
+
+    /// Append an entry for the given project to the index file.  `err` is the
+    /// error that occurred while running the command on the project, if any.
+    fn record(&self, p: &Project, err: Option<&anyhow::Error>) -> anyhow::Result<()> {
+        let status = match err {
+            None => String::from("0"),
+            Some(e) => match e.downcast_ref::<CommandError>() {
+                Some(CommandError::Exit { rc, .. }) => rc
+                    .code()
+                    .map_or_else(|| rc.to_string(), |code| code.to_string()),
+                _ => String::from("error"),
+            },
+        };
+        let path = self.dirpath.join(LOG_INDEX_FILE);
+        let mut index = fs_err::OpenOptions::new().append(true).open(&path)?;
+        writeln!(index, "{}\t{status}\t{}", p.name(), LogDir::log_name(p))
+            .with_context(|| format!("failed to write to {}", path.display()))?;
         Ok(())
     }
 }
@@ -1,18 +1,42 @@
-use super::ForAll;
-use crate::github::{CreateLabel, CreatePullRequest, GitHub};
-use crate::logging::logproject;
+use super::{ForAll, restore_after};
+use crate::cmd::{CapturedOutput, CommandLine};
+use crate::forge::{Forges, RemoteRepo};
+use crate::github::{CreateLabel, CreatePullRequest};
+use crate::logging::{logerror, logproject};
 use crate::project::Project;
+use crate::sweep::{SweepLog, SweepRecord};
+use crate::template::Template;
 use crate::util::{RunOpts, Runner};
+use anyhow::Context;
 use clap::Args;
 use rand::{Rng, rng, seq::IndexedRandom};
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
 use std::path::PathBuf;
 use time::{OffsetDateTime, format_description::FormatItem, macros::format_description};
 
 static DEFAULT_BRANCH_FORMAT: &[FormatItem<'_>] =
     format_description!("forall-runpr-[year][month][day][hour][minute][second]");
 
+/// Placeholders that may be used in the commit message, PR title, and PR body
+static TEMPLATE_PLACEHOLDERS: &[&str] = &[
+    "name",
+    "ghrepo",
+    "language",
+    "default_branch",
+    "branch",
+    "output",
+    "diffstat",
+];
+
+/// The maximum number of characters allowed in a PR body by GitHub
+const MAX_PR_BODY_LEN: usize = 65536;
+
+/// The maximum number of characters of each captured output stream and the
+/// diff summary to include in a PR body
+const MAX_DETAILS_OUTPUT_LEN: usize = 15000;
+
 // These are the "default colors" listed when creating a label via GitHub's web
 // UI as of 2023-09-24:
 static NEW_LABEL_COLORS: &[&str] = &[
@@ -20,15 +44,46 @@ static NEW_LABEL_COLORS: &[&str] = &[
     "c5def5", "d4c5f9", "d93f0b", "e99695", "f9d0c4", "fbca04", "fef2c0",
 ];
 
-/// Run a command on each project and submit the changes as a GitHub pull
-/// request.
+/// Run a command on each project and submit the changes as a pull request on
+/// GitHub, GitLab, or Gitea/Forgejo.
 ///
-/// Only projects that have non-archived GitHub remotes are considered.
+/// Only projects whose `origin` remotes point to non-archived repositories on
+/// recognized forges are considered.
 ///
 /// The command is run with the current working directory set to each
 /// respective project's directory.
+///
+/// With `--template`, the commit message, PR title, and PR body may contain
+/// placeholders of the form `{name}`, which are replaced with per-project
+/// values: `{name}`, `{ghrepo}` (the repository path on the forge),
+/// `{language}`, `{default_branch}`, `{branch}`, `{output}` (the command's
+/// stdout), and `{diffstat}` (the output of `git diff --stat` for the
+/// changes).  `{output}` and `{diffstat}` are truncated to their last 15000
+/// characters.  Use `{{` and `}}` for literal braces.
 #[derive(Args, Clone, Debug, Eq, PartialEq)]
 pub(crate) struct RunPr {
+    /// Commit message [required]
+    #[arg(short, long, required = true, value_name = "TEXT")]
+    message: String,
+
+    #[command(flatten)]
+    pr_opts: PrOpts,
+
+    #[command(flatten)]
+    pub(crate) run_opts: RunOpts,
+}
+
+impl RunPr {
+    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
+        let runner = Runner::try_from(self.run_opts)?;
+        self.pr_opts.into_forall(&self.message, runner)
+    }
+}
+
+/// Options for committing changes made to projects and submitting them as
+/// pull requests
+#[derive(Args, Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct PrOpts {
     /// Name for the new pull request branch.
     ///
     /// Defaults to `forall-runpr-%Y%m%d%H%M%S`.
@@ -41,9 +96,22 @@ pub(crate) struct RunPr {
     #[arg(short, long, value_name = "NAME")]
     label: Vec<String>,
 
-    /// Commit message [required]
-    #[arg(short, long, required = true, value_name = "TEXT")]
-    message: String,
+    /// Append a collapsible section to the body of each pull request
+    /// containing the executed command line, the command's output, and a
+    /// summary of the committed changes
+    #[arg(long)]
+    include_output: bool,
+
+    /// Don't check out the previously checked-out branch and restore stashed
+    /// changes afterwards
+    #[arg(long)]
+    no_restore: bool,
+
+    /// Run the command and make the commit in a temporary Git worktree
+    /// created from the default branch, leaving the project's checkout
+    /// untouched
+    #[arg(long, conflicts_with = "no_restore")]
+    worktree: bool,
 
     /// Title of the pull requests.  Defaults to the commit message.
     #[arg(short = 'T', long, value_name = "TEXT")]
@@ -59,13 +127,24 @@ pub(crate) struct RunPr {
     #[arg(long, value_name = "NAME")]
     soft_label: Vec<String>,
 
-    #[command(flatten)]
-    pub(crate) run_opts: RunOpts,
+    /// Treat the commit message, PR title, and PR body as templates in which
+    /// `{name}`-style placeholders are replaced with per-project values.
+    /// `{output}` and `{diffstat}` are truncated to their last 15000
+    /// characters.
+    #[arg(long)]
+    template: bool,
 }
 
-impl RunPr {
-    pub(super) fn into_forall(self) -> anyhow::Result<Box<dyn ForAll>> {
-        let github = GitHub::authed()?;
+impl PrOpts {
+    /// Construct a command that makes the given change to each project and
+    /// submits the results as pull requests with the given commit message
+    pub(super) fn into_forall<C: Change + 'static>(
+        self,
+        message: &str,
+        change: C,
+    ) -> anyhow::Result<Box<dyn ForAll>> {
+        let forges = Forges::from_env();
+        let sweeplog = SweepLog::default_location()?;
         let colorgen = RandomColor::new(rng());
         let branch = match self.branch {
             Some(b) => b,
@@ -74,113 +153,305 @@ impl RunPr {
                 .format(&DEFAULT_BRANCH_FORMAT)
                 .expect("formatting a datetime should not fail"),
         };
-        let pr_title = self
-            .pr_title
-            .as_deref()
-            .unwrap_or_else(|| strip_skip(&self.message))
-            .to_owned();
+        let template = self.template;
+        let pr_title = parse_template(
+            self.pr_title
+                .as_deref()
+                .unwrap_or_else(|| strip_skip(message)),
+            "--pr-title",
+            template,
+        )?;
         let pr_body = match self.pr_body_file {
-            Some(p) => Some(fs_err::read_to_string(p)?),
+            Some(p) => Some(parse_template(
+                &fs_err::read_to_string(p)?,
+                "--pr-body-file",
+                template,
+            )?),
             None => None,
         };
-        let runner = Runner::try_from(self.run_opts)?;
+        let message = parse_template(message, "--message", template)?;
         Ok(Box::new(RunPrForAll {
-            github,
+            forges,
+            sweeplog,
             colorgen,
             branch,
             pr_title,
             pr_body,
-            runner,
+            change,
             label: self.label,
             soft_label: self.soft_label,
-            message: self.message,
+            message,
+            include_output: self.include_output,
+            no_restore: self.no_restore,
+            worktree: self.worktree,
         }))
     }
 }
 
-#[derive(Clone, Debug)]
-struct RunPrForAll {
-    github: GitHub,
+/// A change to make to each project before submitting it as a pull request
+pub(super) trait Change {
+    /// Make the change in the given project's directory.  If `capture` is
+    /// true, the output of the change is captured and returned.
+    fn apply(&self, p: &Project, capture: bool) -> anyhow::Result<Option<CapturedOutput>>;
+
+    /// Return a command line describing the change, for inclusion in pull
+    /// request bodies
+    fn cmdline(&self, p: &Project) -> CommandLine;
+}
+
+impl Change for Runner {
+    fn apply(&self, p: &Project, capture: bool) -> anyhow::Result<Option<CapturedOutput>> {
+        if capture {
+            Ok(Some(self.run_capture(p)?))
+        } else {
+            self.run(p)?;
+            Ok(None)
+        }
+    }
+
+    fn cmdline(&self, p: &Project) -> CommandLine {
+        Runner::cmdline(self, p)
+    }
+}
+
+#[derive(Debug)]
+struct RunPrForAll<C> {
+    forges: Forges,
+    sweeplog: SweepLog,
     colorgen: RandomColor<rand::rngs::ThreadRng>,
     branch: String,
-    pr_title: String,
-    pr_body: Option<String>,
-    runner: Runner,
+    pr_title: Template,
+    pr_body: Option<Template>,
+    change: C,
     label: Vec<String>,
     soft_label: Vec<String>,
-    message: String,
+    message: Template,
+    include_output: bool,
+    no_restore: bool,
+    worktree: bool,
+}
+
+impl<C> RunPrForAll<C> {
+    fn uses_placeholder(&self, placeholder: &str) -> bool {
+        self.message.uses(placeholder)
+            || self.pr_title.uses(placeholder)
+            || self.pr_body.as_ref().is_some_and(|t| t.uses(placeholder))
+    }
 }
 
-impl ForAll for RunPrForAll {
+impl<C: Change> ForAll for RunPrForAll<C> {
     fn run(&mut self, p: &Project) -> anyhow::Result<()> {
-        let Some(ghrepo) = p.ghrepo() else {
-            debug!("{} does not have a GitHub repository; skipping", p.name());
+        let Some(remote) = p.remote() else {
+            debug!("{} does not have a remote repository; skipping", p.name());
+            return Ok(());
+        };
+        let Some(forge) = self.forges.get(remote)? else {
+            debug!(
+                "Repository for {} is not on a recognized forge; skipping",
+                p.name()
+            );
             return Ok(());
         };
-        if self.github.get_repository(ghrepo)?.archived {
+        if forge.get_repository(remote)?.archived {
             debug!("Repository for {} is archived; skipping", p.name());
             return Ok(());
         }
         logproject(p);
         let defbranch = p.default_branch()?;
-        p.stash()?;
+        if self.worktree {
+            self.submit_in_worktree(p, remote, &defbranch)
+        } else if self.no_restore {
+            p.stash()?;
+            self.submit(p, remote, &defbranch)
+        } else {
+            let checkpoint = p.checkpoint()?;
+            let r = self.submit(p, remote, &defbranch);
+            restore_after(p, &checkpoint, r)
+        }
+    }
+}
+
+impl<C: Change> RunPrForAll<C> {
+    /// Run the command on a new branch in the project's checkout and submit
+    /// the changes as a pull request
+    fn submit(&mut self, p: &Project, remote: &RemoteRepo, defbranch: &str) -> anyhow::Result<()> {
         p.runcmd("git")
             .arg("checkout")
             .arg("-b")
             .arg(&self.branch)
             .arg(defbranch)
             .run()?;
-        self.runner.run(p)?;
-        p.runcmd("git").args(["add", "."]).run()?;
-        // XXX: When adding support for commands that commit, also check
-        //      whether $branch is ahead of $defbranch.
-        if !p.has_staged_changes()? {
-            info!("No changes");
+        if !self.make_pr(p, remote, defbranch)? {
             p.runcmd("git").arg("checkout").arg(defbranch).run()?;
             p.runcmd("git")
                 .args(["branch", "-d"])
                 .arg(&self.branch)
                 .run()?;
-            return Ok(());
+        }
+        Ok(())
+    }
+
+    /// Run the command on a new branch in a temporary worktree of the
+    /// project's repository and submit the changes as a pull request.  The
+    /// worktree is removed afterwards.
+    fn submit_in_worktree(
+        &mut self,
+        p: &Project,
+        remote: &RemoteRepo,
+        defbranch: &str,
+    ) -> anyhow::Result<()> {
+        let tmpdir = tempfile::Builder::new()
+            .prefix("forall-worktree-")
+            .tempdir()
+            .context("failed to create temporary directory")?;
+        let wtpath = tmpdir.path().join(p.name());
+        p.runcmd("git")
+            .args(["worktree", "add", "-b"])
+            .arg(&self.branch)
+            .arg(&wtpath)
+            .arg(defbranch)
+            .run()?;
+        let r = self.make_pr(&p.with_dirpath(wtpath.clone()), remote, defbranch);
+        let removed = p
+            .runcmd("git")
+            .args(["worktree", "remove", "--force"])
+            .arg(&wtpath)
+            .run();
+        let changed = match (r, removed) {
+            (Ok(changed), Ok(())) => changed,
+            (Ok(_), Err(e)) => return Err(e.into()),
+            (Err(e), Ok(())) => return Err(e),
+            (Err(e), Err(e2)) => {
+                logerror(anyhow::Error::new(e2).context("Failed to remove worktree"));
+                return Err(e);
+            }
+        };
+        if !changed {
+            p.runcmd("git")
+                .args(["branch", "-D"])
+                .arg(&self.branch)
+                .run()?;
+        }
+        Ok(())
+    }
+
+    /// Run the command on the currently checked-out PR branch, commit any
+    /// changes, push them, and create a pull request.  Returns `false` if the
+    /// command did not produce any changes.
+    fn make_pr(
+        &mut self,
+        p: &Project,
+        remote: &RemoteRepo,
+        defbranch: &str,
+    ) -> anyhow::Result<bool> {
+        let output = self
+            .change
+            .apply(p, self.include_output || self.uses_placeholder("output"))?;
+        p.runcmd("git").args(["add", "."]).run()?;
+        // XXX: When adding support for commands that commit, also check
+        //      whether $branch is ahead of $defbranch.
+        if !p.has_staged_changes()? {
+            info!("No changes");
+            return Ok(false);
+        }
+        let mut vars = HashMap::from([
+            ("name", p.name().to_owned()),
+            ("ghrepo", remote.to_string()),
+            ("language", p.language().to_string()),
+            ("default_branch", defbranch.to_owned()),
+            ("branch", self.branch.clone()),
+        ]);
+        // Cap the output and diff summary so that PR bodies using them stay
+        // within GitHub's length limit
+        if let Some(ref output) = output {
+            vars.insert(
+                "output",
+                truncate_start(output.stdout.trim_end(), MAX_DETAILS_OUTPUT_LEN).into_owned(),
+            );
+        }
+        let diffstat = if self.include_output || self.uses_placeholder("diffstat") {
+            Some(p.readcmd("git", ["diff", "--cached", "--stat"])?)
+        } else {
+            None
+        };
+        if let Some(ref diffstat) = diffstat {
+            vars.insert(
+                "diffstat",
+                truncate_start(diffstat, MAX_DETAILS_OUTPUT_LEN).into_owned(),
+            );
         }
         p.runcmd("git")
             .args(["commit", "-m"])
-            .arg(&self.message)
+            .arg(self.message.render(&vars))
             .run()?;
         p.runcmd("git")
             .args(["push", "--set-upstream", "origin"])
             .arg(&self.branch)
             .run()?;
-        let pr = self.github.create_pull_request(
-            ghrepo,
+        let mut body = self.pr_body.as_ref().map(|t| t.render(&vars));
+        if self.include_output {
+            let base = body.unwrap_or_default();
+            let base = base.trim_end();
+            // Leave room for the blank line separating the details from the
+            // rest of the body
+            let room = MAX_PR_BODY_LEN.saturating_sub(base.chars().count() + 2);
+            let details = render_details(
+                &self.change.cmdline(p),
+                &output.unwrap_or_default(),
+                diffstat.as_deref().unwrap_or_default(),
+                room,
+            );
+            body = match details {
+                Some(details) if base.is_empty() => Some(details),
+                Some(details) => Some(format!("{base}\n\n{details}")),
+                None => {
+                    info!("PR body is too long to include command output; omitting");
+                    Some(base.to_owned())
+                }
+            };
+        }
+        let Some(forge) = self.forges.get(remote)? else {
+            anyhow::bail!("{} is not hosted on a recognized forge", remote.host());
+        };
+        let pr = forge.create_pull_request(
+            remote,
             CreatePullRequest {
-                title: Cow::from(&self.pr_title),
+                title: Cow::from(self.pr_title.render(&vars)),
                 head: Cow::from(&self.branch),
                 base: Cow::from(defbranch),
-                body: self.pr_body.as_deref().map(Cow::from),
+                body: body.map(Cow::from),
                 maintainer_can_modify: true,
             },
         )?;
         println!("{}", pr.html_url); // TODO: Improve display?
+        // The commands that read the sweep log only support GitHub
+        if let Some(ghrepo) = p.ghrepo() {
+            self.sweeplog.append(&SweepRecord {
+                branch: self.branch.clone(),
+                project: p.name().to_owned(),
+                repo: ghrepo.clone(),
+                number: pr.number,
+                html_url: pr.html_url.clone(),
+            })?;
+        }
         if !self.label.is_empty() || !self.soft_label.is_empty() {
-            let label_names = self
-                .github
-                .get_label_names(ghrepo)?
+            let label_names = forge
+                .get_label_names(remote)?
                 .into_iter()
                 .map(|s| s.to_ascii_lowercase())
                 .collect::<HashSet<_>>();
             let mut labels = Vec::new();
             for lbl in &self.label {
                 if !label_names.contains(&lbl.to_ascii_lowercase()) {
-                    self.github.create_label(
-                        ghrepo,
+                    forge.create_label(
+                        remote,
                         CreateLabel {
                             name: Cow::from(lbl),
                             color: Cow::from(self.colorgen.generate()),
                             description: None,
                         },
                     )?;
-                    info!("Created label {lbl:?} in {ghrepo}");
+                    info!("Created label {lbl:?} in {remote}");
                 }
                 labels.push(lbl.as_str());
             }
@@ -190,10 +461,10 @@ impl ForAll for RunPrForAll {
                 }
             }
             if !labels.is_empty() {
-                self.github.add_labels_to_pr(ghrepo, pr.number, &labels)?;
+                forge.add_labels_to_pr(remote, pr.number, &labels)?;
             }
         }
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -213,6 +484,81 @@ impl<R: Rng> RandomColor<R> {
     }
 }
 
+/// Render a collapsible Markdown section showing a command line (without its
+/// working directory, so as not to publish local paths), its output, and a
+/// diff summary, with long output truncated so that the section is at most
+/// `max_len` characters long.  Returns `None` if the section cannot be made
+/// to fit.
+fn render_details(
+    cmdline: &CommandLine,
+    output: &CapturedOutput,
+    diffstat: &str,
+    max_len: usize,
+) -> Option<String> {
+    let sections = [
+        ("Command", cmdline.line()),
+        ("Standard output", &output.stdout),
+        ("Standard error", &output.stderr),
+        ("Changes", diffstat),
+    ];
+    let mut limit = MAX_DETAILS_OUTPUT_LEN;
+    loop {
+        let mut s = String::from("<details>\n<summary>Command output</summary>\n\n");
+        for (title, content) in sections {
+            let content = truncate_start(content.trim_end(), limit);
+            if content.is_empty() {
+                continue;
+            }
+            let fence = "`".repeat(longest_backtick_run(&content).max(2) + 1);
+            let _ = write!(s, "{title}:\n\n{fence}\n{content}\n{fence}\n\n");
+        }
+        s.push_str("</details>\n");
+        let excess = s.chars().count().saturating_sub(max_len);
+        if excess == 0 {
+            return Some(s);
+        } else if excess >= limit {
+            return None;
+        }
+        limit -= excess;
+    }
+}
+
+/// If `s` is longer than `limit` characters, remove characters from the start
+/// and replace them with a note saying how many characters were removed
+fn truncate_start(s: &str, limit: usize) -> Cow<'_, str> {
+    let len = s.chars().count();
+    if len <= limit {
+        return Cow::from(s);
+    }
+    let omitted = len - limit;
+    let start = s.char_indices().nth(omitted).map_or(s.len(), |(i, _)| i);
+    Cow::from(format!(
+        "[... {omitted} characters omitted ...]\n{}",
+        &s[start..]
+    ))
+}
+
+fn longest_backtick_run(s: &str) -> usize {
+    s.split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Parse the value of the given option as a template if `template` is true;
+/// otherwise, return a template that renders to the value verbatim
+fn parse_template(s: &str, option: &str, template: bool) -> anyhow::Result<Template> {
+    if !template {
+        return Ok(Template::literal(s));
+    }
+    let tmpl = s
+        .parse::<Template>()
+        .with_context(|| format!("invalid {option} value"))?;
+    tmpl.check_placeholders(TEMPLATE_PLACEHOLDERS)
+        .with_context(|| format!("invalid {option} value"))?;
+    Ok(tmpl)
+}
+
 fn strip_skip(mut s: &str) -> &str {
     // <https://docs.github.com/en/actions/managing-workflow-runs-and-deployments/managing-workflow-runs/skipping-workflow-runs>
     // TODO: Delete skip strings in the middle of a commit message
@@ -254,4 +600,80 @@ mod tests {
     fn test_strip_skip(#[case] before: &str, #[case] after: &str) {
         assert_eq!(strip_skip(before), after);
     }
+
+    #[rstest]
+    #[case("short", 10, "short")]
+    #[case("exactly10!", 10, "exactly10!")]
+    #[case("0123456789abc", 10, "[... 3 characters omitted ...]\n3456789abc")]
+    #[case("ééééé", 2, "[... 3 characters omitted ...]\néé")]
+    fn test_truncate_start(#[case] s: &str, #[case] limit: usize, #[case] truncated: &str) {
+        assert_eq!(truncate_start(s, limit), truncated);
+    }
+
+    #[test]
+    fn test_render_details() {
+        let cmdline = crate::cmd::CommandPlus::new("cargo")
+            .args(["fmt", "--all"])
+            .current_dir("/home/user/project")
+            .cmdline()
+            .clone();
+        let output = CapturedOutput {
+            stdout: String::from("Formatted ```code```\n"),
+            stderr: String::new(),
+        };
+        let diffstat = " src/main.rs | 2 +-\n 1 file changed, 1 insertion(+), 1 deletion(-)\n";
+        assert_eq!(
+            render_details(&cmdline, &output, diffstat, MAX_PR_BODY_LEN).as_deref(),
+            Some(concat!(
+                "<details>\n",
+                "<summary>Command output</summary>\n",
+                "\n",
+                "Command:\n",
+                "\n",
+                "```\n",
+                "cargo fmt --all\n",
+                "```\n",
+                "\n",
+                "Standard output:\n",
+                "\n",
+                "````\n",
+                "Formatted ```code```\n",
+                "````\n",
+                "\n",
+                "Changes:\n",
+                "\n",
+                "```\n",
+                " src/main.rs | 2 +-\n",
+                " 1 file changed, 1 insertion(+), 1 deletion(-)\n",
+                "```\n",
+                "\n",
+                "</details>\n",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_render_details_truncated() {
+        let cmdline = crate::cmd::CommandPlus::new("cat")
+            .arg("log.txt")
+            .cmdline()
+            .clone();
+        let output = CapturedOutput {
+            stdout: "x".repeat(1000),
+            stderr: String::new(),
+        };
+        let details = render_details(&cmdline, &output, "", 500).unwrap();
+        assert_eq!(details.chars().count(), 500);
+        assert!(details.contains("characters omitted"));
+        assert!(details.ends_with("xxx\n```\n\n</details>\n"));
+        assert_eq!(render_details(&cmdline, &output, "", 50), None);
+    }
+
+    #[rstest]
+    #[case("no ticks", 0)]
+    #[case("a `b` c", 1)]
+    #[case("```rust\n```` x", 4)]
+    fn test_longest_backtick_run(#[case] s: &str, #[case] run: usize) {
+        assert_eq!(longest_backtick_run(s), run);
+    }
 }
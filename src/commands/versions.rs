@@ -0,0 +1,120 @@
+use super::ForAll;
+use crate::changelog::{Changelog, ChangelogSection};
+use crate::project::Project;
+use crate::table::Table;
+use clap::Args;
+use fs_err::PathExt;
+use serde::Serialize;
+
+/// Show each project's version and whether it has unreleased changes
+///
+/// For each project, the version declared in `Cargo.toml` or
+/// `pyproject.toml`, the most recent tag reachable from `HEAD`, the number of
+/// commits since that tag, and whether the topmost section of `CHANGELOG.md`
+/// is marked as in development are shown.  Projects with commits since the
+/// latest tag are considered to have unreleased changes.
+#[derive(Args, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Versions {
+    /// Output JSON
+    #[arg(short = 'J', long)]
+    json: bool,
+
+    /// Only show projects with unreleased changes
+    #[arg(long)]
+    unreleased: bool,
+}
+
+impl Versions {
+    pub(super) fn into_forall(self) -> Box<dyn ForAll> {
+        Box::new(VersionsForAll {
+            json: self.json,
+            unreleased: self.unreleased,
+            table: Table::new([
+                "PROJECT",
+                "VERSION",
+                "LATEST TAG",
+                "COMMITS SINCE",
+                "CHANGELOG",
+                "",
+            ]),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct VersionsForAll {
+    json: bool,
+    unreleased: bool,
+    table: Table,
+}
+
+impl ForAll for VersionsForAll {
+    fn run(&mut self, p: &Project) -> anyhow::Result<()> {
+        let latest_tag = p.latest_tag()?;
+        let commits_since_tag = p.count_commits(latest_tag.as_deref())?;
+        let unreleased = commits_since_tag > 0;
+        if self.unreleased && !unreleased {
+            return Ok(());
+        }
+        let path = p.dirpath().join("CHANGELOG.md");
+        let changelog_in_development = if path.fs_err_try_exists()? {
+            Changelog::parse(&fs_err::read_to_string(&path)?)
+                .latest()
+                .is_some_and(ChangelogSection::in_development)
+        } else {
+            false
+        };
+        let info = VersionInfo {
+            project: p.name(),
+            version: p.version(),
+            latest_tag: latest_tag.as_deref(),
+            commits_since_tag,
+            changelog_in_development,
+            unreleased,
+        };
+        if self.json {
+            println!(
+                "{}",
+                serde_json::to_string(&info).expect("JSONification should not fail")
+            );
+        } else {
+            self.table.push(info.to_row());
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if !self.json && !self.table.is_empty() {
+            print!("{}", self.table);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct VersionInfo<'a> {
+    project: &'a str,
+    version: Option<&'a str>,
+    latest_tag: Option<&'a str>,
+    commits_since_tag: u64,
+    changelog_in_development: bool,
+    unreleased: bool,
+}
+
+impl VersionInfo<'_> {
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.project.to_owned(),
+            self.version.unwrap_or("-").to_owned(),
+            self.latest_tag.unwrap_or("-").to_owned(),
+            self.commits_since_tag.to_string(),
+            if self.changelog_in_development {
+                "in development"
+            } else {
+                "-"
+            }
+            .to_owned(),
+            if self.unreleased { "unreleased" } else { "" }.to_owned(),
+        ]
+    }
+}
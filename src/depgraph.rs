@@ -0,0 +1,355 @@
+use crate::project::{Language, PackageDeps, Project, normalize_name};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Write};
+use thiserror::Error;
+
+/// A graph of the dependencies between a set of projects.  There is an edge
+/// from project A to project B if A depends on a package provided by B
+/// (where both projects are written in the same language).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct DepGraph {
+    /// Project names, in the order the projects were supplied
+    names: Vec<String>,
+
+    /// For each project (by index), the indices of the projects it depends
+    /// on
+    deps: Vec<BTreeSet<usize>>,
+
+    /// For each project (by index), the normalized names of all packages it
+    /// depends on, whether provided by another project or not
+    requires: Vec<Vec<String>>,
+}
+
+impl DepGraph {
+    /// Build the dependency graph for the given projects.  Dependencies on
+    /// packages not provided by any of the projects are ignored.
+    pub(crate) fn build(projects: &[Project]) -> anyhow::Result<DepGraph> {
+        let mut nodes = Vec::with_capacity(projects.len());
+        for p in projects {
+            nodes.push((p.name().to_owned(), p.language(), p.package_deps()?));
+        }
+        Ok(DepGraph::from_deps(nodes))
+    }
+
+    fn from_deps(nodes: Vec<(String, Language, PackageDeps)>) -> DepGraph {
+        let mut providers = HashMap::new();
+        for (i, (_, lang, pd)) in nodes.iter().enumerate() {
+            for pkg in &pd.provides {
+                providers.entry((*lang, pkg.as_str())).or_insert(i);
+            }
+        }
+        let deps = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (_, lang, pd))| {
+                pd.requires
+                    .iter()
+                    .filter_map(|pkg| providers.get(&(*lang, pkg.as_str())).copied())
+                    .filter(|&j| j != i)
+                    .collect()
+            })
+            .collect();
+        let (names, requires) = nodes
+            .into_iter()
+            .map(|(name, _, pd)| (name, pd.requires))
+            .unzip();
+        DepGraph {
+            names,
+            deps,
+            requires,
+        }
+    }
+
+    /// Return the subgraph consisting of the projects with the given names,
+    /// which must all be in the graph, in the order given
+    pub(crate) fn subgraph<'a, I>(&self, names: I) -> DepGraph
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let indices = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect::<HashMap<_, _>>();
+        let kept = names
+            .into_iter()
+            .map(|name| indices[name])
+            .collect::<Vec<_>>();
+        let renumbered = kept
+            .iter()
+            .enumerate()
+            .map(|(new, &old)| (old, new))
+            .collect::<HashMap<_, _>>();
+        DepGraph {
+            names: kept.iter().map(|&i| self.names[i].clone()).collect(),
+            deps: kept
+                .iter()
+                .map(|&i| {
+                    self.deps[i]
+                        .iter()
+                        .filter_map(|j| renumbered.get(j).copied())
+                        .collect()
+                })
+                .collect(),
+            requires: kept.iter().map(|&i| self.requires[i].clone()).collect(),
+        }
+    }
+
+    /// Return the indices of the projects in an order in which each project
+    /// comes after all of the projects it depends on.  Whenever more than
+    /// one project could come next, the one supplied earliest is chosen.
+    pub(crate) fn topo_order(&self) -> Result<Vec<usize>, CycleError> {
+        let mut remaining = self.deps.iter().map(BTreeSet::len).collect::<Vec<_>>();
+        let mut dependents = vec![Vec::new(); self.names.len()];
+        for (i, ds) in self.deps.iter().enumerate() {
+            for &j in ds {
+                dependents[j].push(i);
+            }
+        }
+        let mut ready = (0..self.names.len())
+            .filter(|&i| remaining[i] == 0)
+            .collect::<BTreeSet<_>>();
+        let mut order = Vec::with_capacity(self.names.len());
+        while let Some(i) = ready.pop_first() {
+            order.push(i);
+            for &k in &dependents[i] {
+                remaining[k] -= 1;
+                if remaining[k] == 0 {
+                    ready.insert(k);
+                }
+            }
+        }
+        if order.len() < self.names.len() {
+            // The unordered projects are those in cycles plus those that
+            // depend on them; prune the latter for the error message
+            let mut blocked = (0..self.names.len())
+                .filter(|&i| remaining[i] > 0)
+                .collect::<BTreeSet<_>>();
+            while let Some(i) = blocked
+                .iter()
+                .copied()
+                .find(|&i| !dependents[i].iter().any(|k| blocked.contains(k)))
+            {
+                blocked.remove(&i);
+            }
+            let projects = blocked.into_iter().map(|i| self.names[i].clone()).collect();
+            Err(CycleError { projects })
+        } else {
+            Ok(order)
+        }
+    }
+
+    /// Return the names of the projects that depend on the package `name`.
+    /// If `transitive` is true, projects that depend on such projects (and so
+    /// on) are also included.
+    pub(crate) fn dependents_of(&self, name: &str, transitive: bool) -> BTreeSet<&str> {
+        let name = normalize_name(name);
+        let mut found = (0..self.names.len())
+            .filter(|&i| self.requires[i].contains(&name))
+            .collect::<BTreeSet<_>>();
+        if transitive {
+            let mut queue = found.iter().copied().collect::<Vec<_>>();
+            while let Some(j) = queue.pop() {
+                for (i, ds) in self.deps.iter().enumerate() {
+                    if ds.contains(&j) && found.insert(i) {
+                        queue.push(i);
+                    }
+                }
+            }
+        }
+        found.into_iter().map(|i| self.names[i].as_str()).collect()
+    }
+
+    /// Render the graph in Graphviz DOT format
+    pub(crate) fn to_dot(&self) -> String {
+        let mut s = String::from("digraph dependencies {\n");
+        for name in &self.names {
+            let _ = writeln!(s, "    {};", DotId(name));
+        }
+        for (i, ds) in self.deps.iter().enumerate() {
+            for &j in ds {
+                let _ = writeln!(
+                    s,
+                    "    {} -> {};",
+                    DotId(&self.names[i]),
+                    DotId(&self.names[j])
+                );
+            }
+        }
+        s.push_str("}\n");
+        s
+    }
+
+    pub(crate) fn to_json(&self) -> GraphJson<'_> {
+        GraphJson {
+            nodes: self.names.iter().map(String::as_str).collect(),
+            edges: self
+                .deps
+                .iter()
+                .enumerate()
+                .flat_map(|(i, ds)| {
+                    ds.iter().map(move |&j| Edge {
+                        from: &self.names[i],
+                        to: &self.names[j],
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("dependency cycle among projects: {}", projects.join(", "))]
+pub(crate) struct CycleError {
+    projects: Vec<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct GraphJson<'a> {
+    nodes: Vec<&'a str>,
+    edges: Vec<Edge<'a>>,
+}
+
+/// An edge from a project to a project that it depends on
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct Edge<'a> {
+    from: &'a str,
+    to: &'a str,
+}
+
+/// Display a string as a quoted DOT identifier
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct DotId<'a>(&'a str);
+
+impl fmt::Display for DotId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            if c == '"' || c == '\\' {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        f.write_char('"')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(
+        name: &str,
+        lang: Language,
+        provides: &[&str],
+        requires: &[&str],
+    ) -> (String, Language, PackageDeps) {
+        (
+            name.to_owned(),
+            lang,
+            PackageDeps {
+                provides: provides.iter().map(|&s| s.to_owned()).collect(),
+                requires: requires.iter().map(|&s| s.to_owned()).collect(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_topo_order() {
+        let graph = DepGraph::from_deps(vec![
+            node(
+                "app",
+                Language::Rust,
+                &["app"],
+                &["core", "macros", "serde"],
+            ),
+            node(
+                "core",
+                Language::Rust,
+                &["core", "core-derive"],
+                &["macros"],
+            ),
+            node("macros", Language::Rust, &["macros"], &[]),
+            node("pyapp", Language::Python, &["pyapp"], &["app", "pylib"]),
+            node("pylib", Language::Python, &["pylib"], &[]),
+        ]);
+        assert_eq!(graph.topo_order().unwrap(), [2, 1, 0, 4, 3]);
+        assert_eq!(
+            graph.to_dot(),
+            concat!(
+                "digraph dependencies {\n",
+                "    \"app\";\n",
+                "    \"core\";\n",
+                "    \"macros\";\n",
+                "    \"pyapp\";\n",
+                "    \"pylib\";\n",
+                "    \"app\" -> \"core\";\n",
+                "    \"app\" -> \"macros\";\n",
+                "    \"core\" -> \"macros\";\n",
+                "    \"pyapp\" -> \"pylib\";\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_dependents_of() {
+        let graph = DepGraph::from_deps(vec![
+            node("app", Language::Rust, &["app"], &["core"]),
+            node("core", Language::Rust, &["core"], &["my-lib"]),
+            node("my-lib", Language::Rust, &["my-lib"], &[]),
+            node("other", Language::Rust, &["other"], &["my-lib", "serde"]),
+            node("unrelated", Language::Rust, &["unrelated"], &["serde"]),
+        ]);
+        assert_eq!(
+            graph.dependents_of("my_lib", false),
+            BTreeSet::from(["core", "other"])
+        );
+        assert_eq!(
+            graph.dependents_of("My-Lib", true),
+            BTreeSet::from(["app", "core", "other"])
+        );
+        assert_eq!(
+            graph.dependents_of("serde", true),
+            BTreeSet::from(["other", "unrelated"])
+        );
+    }
+
+    #[test]
+    fn test_subgraph() {
+        let graph = DepGraph::from_deps(vec![
+            node("a", Language::Rust, &["a"], &["b"]),
+            node("b", Language::Rust, &["b"], &["a"]),
+            node("c", Language::Rust, &["c"], &["a", "d"]),
+            node("d", Language::Rust, &["d"], &[]),
+        ]);
+        let sub = graph.subgraph(["c", "a", "d"]);
+        assert_eq!(sub.topo_order().unwrap(), [1, 2, 0]);
+        assert_eq!(
+            sub.to_dot(),
+            concat!(
+                "digraph dependencies {\n",
+                "    \"c\";\n",
+                "    \"a\";\n",
+                "    \"d\";\n",
+                "    \"c\" -> \"a\";\n",
+                "    \"c\" -> \"d\";\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let graph = DepGraph::from_deps(vec![
+            node("a", Language::Rust, &["a"], &["b"]),
+            node("b", Language::Rust, &["b"], &["a"]),
+            node("c", Language::Rust, &["c"], &["a"]),
+            node("d", Language::Rust, &["d"], &[]),
+        ]);
+        let e = graph.topo_order().unwrap_err();
+        assert_eq!(e.to_string(), "dependency cycle among projects: a, b");
+    }
+}
@@ -1,5 +1,9 @@
+use crate::cmd::{CommandKind, CommandPlus};
+use crate::depgraph::DepGraph;
+use crate::github::{CiStatus, GitHub, RepoCache, Repository, Visibility};
+use crate::logging::logwarning;
 use crate::project::{Language, Project};
-use crate::util::get_shell;
+use crate::util::{get_shell, xdg_dir};
 use anyhow::Context;
 use clap::Args;
 use fs_err::PathExt;
@@ -7,8 +11,67 @@ use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
+/// The results of project discovery
+#[derive(Debug, Default)]
+pub(crate) struct Discovery {
+    /// The projects that were found and passed the filters
+    pub(crate) projects: Vec<Project>,
+
+    /// Git repositories that could not be loaded as projects
+    pub(crate) problems: Vec<DiscoveryProblem>,
+}
+
+impl Discovery {
+    /// Load the project in the Git repository at `dirpath` and add it to
+    /// `projects`; filters are applied afterwards by [`Finder::findall()`].
+    /// If the repository does not contain a recognized project or its
+    /// manifest cannot be loaded, the problem is recorded in `problems`
+    /// instead.
+    fn load(&mut self, dirpath: PathBuf) {
+        match Project::try_for_dirpath(dirpath.clone()) {
+            Ok(Some(p)) => self.projects.push(p),
+            Ok(None) => self.problems.push(DiscoveryProblem {
+                dirpath,
+                kind: ProblemKind::Unrecognized,
+            }),
+            Err(e) => self.problems.push(DiscoveryProblem {
+                dirpath,
+                kind: ProblemKind::Invalid(e),
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DiscoveryProblem {
+    pub(crate) dirpath: PathBuf,
+    pub(crate) kind: ProblemKind,
+}
+
+#[derive(Debug)]
+pub(crate) enum ProblemKind {
+    /// The repository's manifest could not be read or parsed
+    Invalid(anyhow::Error),
+
+    /// The repository does not contain a recognized type of project
+    Unrecognized,
+}
+
 #[derive(Args, Clone, Debug, Eq, PartialEq)]
 pub(crate) struct Finder {
+    /// Only operate on projects whose GitHub repositories are archived
+    #[arg(long, overrides_with = "not_archived", global = true)]
+    archived: bool,
+
+    /// Only operate on projects whose GitHub repositories are not archived
+    #[arg(long, global = true)]
+    not_archived: bool,
+
+    /// Only operate on projects whose GitHub repositories' default branches
+    /// are failing CI
+    #[arg(long, global = true)]
+    ci_failing: bool,
+
     /// Only operate on projects currently on their default branch
     #[arg(short = 'D', long, overrides_with = "no_def_branch", global = true)]
     def_branch: bool,
@@ -17,6 +80,17 @@ pub(crate) struct Finder {
     #[arg(long, global = true)]
     no_def_branch: bool,
 
+    /// Only operate on projects that depend on the given package.  Can be
+    /// specified multiple times to select projects that depend on any of the
+    /// packages.
+    #[arg(long, global = true, value_name = "NAME")]
+    depends_on: Vec<String>,
+
+    /// With `--depends-on`, also operate on projects that depend on the
+    /// package indirectly via other discovered projects
+    #[arg(long, requires = "depends_on", global = true)]
+    transitive: bool,
+
     /// Don't operate on the given project.  Can be specified multiple times.
     #[arg(long, global = true, value_name = "NAME")]
     exclude: Vec<String>,
@@ -28,6 +102,37 @@ pub(crate) struct Finder {
     #[arg(short, long, value_name = "SHELLCMD", global = true)]
     filter: Option<String>,
 
+    /// Directory in which to clone repositories selected by `--gh-org`,
+    /// `--gh-user`, and `--gh-search`  [default:
+    /// `$XDG_CACHE_HOME/forall/repos`]
+    #[arg(long, global = true, value_name = "DIRPATH")]
+    clone_dir: Option<PathBuf>,
+
+    /// Operate on the non-archived repositories of the given GitHub
+    /// organization, cloning them if necessary.  Can be specified multiple
+    /// times.
+    #[arg(long, global = true, value_name = "ORG")]
+    gh_org: Vec<String>,
+
+    /// Operate on the non-archived repositories matching the given GitHub
+    /// repository search query (e.g., "topic:cli user:octocat"), cloning them
+    /// if necessary.  Can be specified multiple times.
+    #[arg(long, global = true, value_name = "QUERY")]
+    gh_search: Vec<String>,
+
+    /// Operate on the non-archived repositories owned by the given GitHub
+    /// user, cloning them if necessary.  Can be specified multiple times.
+    #[arg(long, global = true, value_name = "USER")]
+    gh_user: Vec<String>,
+
+    /// Only operate on projects whose GitHub repositories are forks
+    #[arg(long, overrides_with = "not_fork", global = true)]
+    fork: bool,
+
+    /// Only operate on projects whose GitHub repositories are not forks
+    #[arg(long, global = true)]
+    not_fork: bool,
+
     /// Only operate on projects that have GitHub remotes
     #[arg(long, overrides_with = "no_github", global = true)]
     has_github: bool,
@@ -52,10 +157,26 @@ pub(crate) struct Finder {
     language: Option<Language>,
 
     /// Directory to traverse for projects.  Can be specified multiple times to
-    /// traverse multiple directories.  [default: current directory]
+    /// traverse multiple directories.  [default: current directory, unless
+    /// `--gh-org`, `--gh-user`, or `--gh-search` is given]
     #[arg(short = 'R', long, global = true, value_name = "DIRPATH")]
     root: Vec<PathBuf>,
 
+    /// Operate on projects in dependency order, so that each project comes
+    /// after the selected projects that it depends on
+    #[arg(long, global = true)]
+    topo: bool,
+
+    /// Only operate on projects whose GitHub repositories have the given
+    /// topic.  Can be specified multiple times to require multiple topics.
+    #[arg(long, global = true, value_name = "NAME")]
+    topic: Vec<String>,
+
+    /// Only operate on projects whose GitHub repositories have the given
+    /// visibility
+    #[arg(long, global = true, value_enum, value_name = "VISIBILITY")]
+    visibility: Option<Visibility>,
+
     /// Only operate on Rust workspaces
     #[arg(short = 'W', long, overrides_with = "not_workspace", global = true)]
     workspace: bool,
@@ -74,22 +195,119 @@ pub(crate) struct Finder {
 }
 
 impl Finder {
-    pub(crate) fn findall(&self) -> anyhow::Result<Vec<Project>> {
-        let roots = if self.root.is_empty() {
+    /// Find all projects under the root directories and in the GitHub
+    /// sources that pass the filters.  Directories that look like projects
+    /// but cannot be loaded do not cause an error but are instead recorded in
+    /// the returned [`Discovery`].
+    pub(crate) fn findall(&self) -> anyhow::Result<Discovery> {
+        let roots = if self.root.is_empty() && !self.uses_github_sources() {
             &vec![std::env::current_dir().context("failed to determine current directory")?]
         } else {
             &self.root
         };
-        let mut projects = Vec::new();
+        let shell = get_shell();
+        let mut cache = RepoCache::new();
+        let mut found = Discovery::default();
         for dirpath in roots {
-            projects.extend(self.find(dirpath, &get_shell())?);
+            self.find(dirpath, &mut found)?;
+        }
+        if self.uses_github_sources() {
+            for subpath in self.clone_github_sources(&mut cache)? {
+                if found.projects.iter().any(|p| p.dirpath() == subpath) {
+                    continue;
+                }
+                found.load(subpath);
+            }
+        }
+        // Dependency chains may pass through projects that are filtered out,
+        // so the graph is built before filtering
+        let graph = if self.depends_on.is_empty() {
+            None
+        } else {
+            Some(DepGraph::build(&found.projects)?)
+        };
+        let selected = graph.as_ref().map(|graph| {
+            self.depends_on
+                .iter()
+                .flat_map(|name| graph.dependents_of(name, self.transitive))
+                .map(ToOwned::to_owned)
+                .collect::<HashSet<_>>()
+        });
+        let mut accepted = Vec::with_capacity(found.projects.len());
+        for p in std::mem::take(&mut found.projects) {
+            if selected.as_ref().is_none_or(|sel| sel.contains(p.name()))
+                && self.accept(&p, &shell, &mut cache)?
+            {
+                accepted.push(p);
+            }
+        }
+        found.projects = accepted;
+        found
+            .projects
+            .sort_unstable_by(|p1, p2| p1.name().cmp(p2.name()));
+        if self.topo {
+            // Reuse the graph built for `--depends-on`, if any, rather than
+            // computing every project's dependencies again
+            let graph = match graph {
+                Some(graph) => graph.subgraph(found.projects.iter().map(Project::name)),
+                None => DepGraph::build(&found.projects)?,
+            };
+            let order = graph.topo_order()?;
+            let mut projects = found.projects.into_iter().map(Some).collect::<Vec<_>>();
+            found.projects = order
+                .into_iter()
+                .filter_map(|i| projects[i].take())
+                .collect();
+        }
+        Ok(found)
+    }
+
+    fn uses_github_sources(&self) -> bool {
+        !(self.gh_org.is_empty() && self.gh_user.is_empty() && self.gh_search.is_empty())
+    }
+
+    /// List the non-archived repositories selected by `--gh-org`,
+    /// `--gh-user`, and `--gh-search`, clone any that have not already been
+    /// cloned into the clone directory, update the rest, and return the paths
+    /// to the clones
+    fn clone_github_sources(&self, cache: &mut RepoCache) -> anyhow::Result<Vec<PathBuf>> {
+        let github = GitHub::authed()?;
+        let clone_dir = match self.clone_dir {
+            Some(ref dir) => dir.clone(),
+            None => xdg_dir("XDG_CACHE_HOME", ".cache")?
+                .join("forall")
+                .join("repos"),
+        };
+        let mut repos = Vec::new();
+        for org in &self.gh_org {
+            repos.extend(github.list_org_repositories(org)?);
         }
-        projects.sort_unstable_by(|p1, p2| p1.name().cmp(p2.name()));
-        Ok(projects)
+        for user in &self.gh_user {
+            repos.extend(github.list_user_repositories(user)?);
+        }
+        for query in &self.gh_search {
+            repos.extend(github.search_repositories(query)?);
+        }
+        let mut seen = HashSet::new();
+        let mut paths = Vec::new();
+        let use_ssh = ssh_configured();
+        for repo in repos {
+            if repo.archived || !seen.insert(repo.id) {
+                continue;
+            }
+            let path = clone_dir.join(&repo.full_name);
+            if path.fs_err_try_exists()? {
+                update_clone(&repo, &path)?;
+            } else {
+                clone_repository(&repo, &path, use_ssh)?;
+            }
+            paths.push(path);
+            cache.insert(repo);
+        }
+        Ok(paths)
     }
 
-    fn find(&self, dirpath: &Path, shell: &OsStr) -> anyhow::Result<Vec<Project>> {
-        let mut projects = Vec::new();
+    fn find(&self, dirpath: &Path, found: &mut Discovery) -> anyhow::Result<()> {
         let ignorefile = dirpath.join(".forall-ignore");
         let exclude = match fs_err::read_to_string(ignorefile) {
             Ok(s) => s.lines().map(ToString::to_string).collect::<HashSet<_>>(),
@@ -110,19 +328,15 @@ impl Finder {
             }
             let subpath = entry.path();
             if subpath.join(".git").fs_err_try_exists()? {
-                if let Some(p) = Project::try_for_dirpath(subpath)?
-                    && self.accept(&p, shell)?
-                {
-                    projects.push(p);
-                }
+                found.load(subpath);
             } else {
-                projects.extend(self.find(&subpath, shell)?);
+                self.find(&subpath, found)?;
             }
         }
-        Ok(projects)
+        Ok(())
     }
 
-    fn accept(&self, p: &Project, shell: &OsStr) -> anyhow::Result<bool> {
+    fn accept(&self, p: &Project, shell: &OsStr, cache: &mut RepoCache) -> anyhow::Result<bool> {
         if self.exclude.iter().any(|name| name == p.name()) {
             return Ok(false);
         }
@@ -156,14 +370,62 @@ impl Finder {
         {
             return Ok(false);
         }
+        if self.uses_github_metadata() {
+            let Some(ghrepo) = p.ghrepo() else {
+                return Ok(false);
+            };
+            let repo = cache.get(ghrepo)?;
+            if self.is_archived().is_some_and(|flag| repo.archived != flag)
+                || self.is_fork().is_some_and(|flag| repo.fork != flag)
+                || self.visibility.is_some_and(|v| repo.visibility() != v)
+                || !self.topic.iter().all(|t| repo.has_topic(t))
+            {
+                return Ok(false);
+            }
+        }
         if let Some(ref cmd) = self.filter
             && !p.check(shell, ["-c", cmd])?
         {
             return Ok(false);
         }
+        if self.ci_failing {
+            let Some(ghrepo) = p.ghrepo() else {
+                return Ok(false);
+            };
+            if cache.default_branch_ci_status(ghrepo)? != CiStatus::Failure {
+                return Ok(false);
+            }
+        }
         Ok(true)
     }
 
+    /// Returns true if any filters that require fetching repository details
+    /// from GitHub are in effect
+    fn uses_github_metadata(&self) -> bool {
+        self.is_archived().is_some()
+            || self.is_fork().is_some()
+            || self.visibility.is_some()
+            || !self.topic.is_empty()
+    }
+
+    fn is_archived(&self) -> Option<bool> {
+        match (self.archived, self.not_archived) {
+            (false, false) => None,
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            (true, true) => unreachable!(),
+        }
+    }
+
+    fn is_fork(&self) -> Option<bool> {
+        match (self.fork, self.not_fork) {
+            (false, false) => None,
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            (true, true) => unreachable!(),
+        }
+    }
+
     fn def_branch(&self) -> Option<bool> {
         match (self.def_branch, self.no_def_branch) {
             (false, false) => None,
@@ -209,3 +471,80 @@ impl Finder {
         }
     }
 }
+
+/// Clone `repo` to `path` via SSH if `use_ssh` is true or via HTTPS otherwise
+fn clone_repository(repo: &Repository, path: &Path, use_ssh: bool) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs_err::create_dir_all(parent)?;
+    }
+    CommandPlus::new("git")
+        .arg("clone")
+        .arg(if use_ssh {
+            &repo.ssh_url
+        } else {
+            &repo.clone_url
+        })
+        .arg(path)
+        .run()
+        .with_context(|| format!("failed to clone {}", repo.full_name))?;
+    Ok(())
+}
+
+/// Fetch the latest changes into an existing clone of `repo` at `path` and,
+/// if possible, fast-forward the checked-out branch.  Clones made shallowly
+/// by older versions of `forall` are converted to full clones.
+fn update_clone(repo: &Repository, path: &Path) -> anyhow::Result<()> {
+    let shallow = CommandPlus::new("git")
+        .args(["rev-parse", "--is-shallow-repository"])
+        .current_dir(path)
+        .kind(CommandKind::Filter)
+        .check_output()?;
+    let mut fetch = CommandPlus::new("git");
+    fetch.args(["fetch", "--prune", "origin"]).current_dir(path);
+    if shallow.trim() == "true" {
+        fetch.arg("--unshallow");
+    }
+    fetch
+        .run()
+        .with_context(|| format!("failed to fetch {}", repo.full_name))?;
+    // Make `origin/HEAD` track the repository's current default branch
+    CommandPlus::new("git")
+        .args(["remote", "set-head", "origin", "--auto"])
+        .current_dir(path)
+        .kind(CommandKind::Filter)
+        .status()?;
+    let merged = CommandPlus::new("git")
+        .args(["merge", "--ff-only", "--quiet"])
+        .current_dir(path)
+        .kind(CommandKind::Filter)
+        .status()?;
+    if !merged.success() {
+        logwarning(&anyhow::anyhow!(
+            "Could not fast-forward clone of {} at {}",
+            repo.full_name,
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Heuristically determine whether the user has SSH set up for cloning from
+/// GitHub, i.e., whether an SSH agent is running or there is a private key in
+/// `~/.ssh`
+fn ssh_configured() -> bool {
+    if std::env::var_os("SSH_AUTH_SOCK").is_some_and(|s| !s.is_empty()) {
+        return true;
+    }
+    let Some(home) = std::env::var_os("HOME") else {
+        return false;
+    };
+    let Ok(entries) = fs_err::read_dir(Path::new(&home).join(".ssh")) else {
+        return false;
+    };
+    entries.filter_map(Result::ok).any(|entry| {
+        entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with("id_") && !name.ends_with(".pub"))
+    })
+}
@@ -0,0 +1,195 @@
+use crate::github::USER_AGENT;
+use anyhow::Context;
+use minigh::Method;
+use serde::{Serialize, de::DeserializeOwned};
+use std::thread::sleep;
+use std::time::Duration;
+use url::Url;
+
+/// Maximum number of times to retry a request that failed with a 429
+/// response
+const MAX_TOO_MANY_REQUESTS_RETRIES: u32 = 3;
+
+/// Time to wait before retrying a request that failed with a 429 response
+/// without a `Retry-After` header
+const TOO_MANY_REQUESTS_WAIT: Duration = Duration::from_secs(60);
+
+/// A minimal client for the JSON REST APIs of forges other than GitHub
+#[derive(Clone, Debug)]
+pub(super) struct ApiClient {
+    agent: ureq::Agent,
+    api_url: Url,
+    auth: String,
+}
+
+impl ApiClient {
+    /// Create a client that resolves request paths relative to `api_url` and
+    /// authenticates with the given bearer token
+    pub(super) fn new(api_url: Url, token: &str) -> ApiClient {
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .redirect_auth_headers(ureq::config::RedirectAuthHeaders::SameHost)
+            .user_agent(USER_AGENT)
+            .accept("application/json")
+            .build()
+            .into();
+        ApiClient {
+            agent,
+            api_url,
+            auth: format!("Bearer {token}"),
+        }
+    }
+
+    pub(super) fn get<U: DeserializeOwned>(&self, path: &str) -> anyhow::Result<U> {
+        let url = self.mkurl(path)?;
+        self.request::<(), U>(Method::Get, url, None)
+            .map(|(body, _)| body)
+    }
+
+    pub(super) fn post<T: Serialize, U: DeserializeOwned>(
+        &self,
+        path: &str,
+        payload: &T,
+    ) -> anyhow::Result<U> {
+        let url = self.mkurl(path)?;
+        self.request(Method::Post, url, Some(payload))
+            .map(|(body, _)| body)
+    }
+
+    pub(super) fn put<T: Serialize, U: DeserializeOwned>(
+        &self,
+        path: &str,
+        payload: &T,
+    ) -> anyhow::Result<U> {
+        let url = self.mkurl(path)?;
+        self.request(Method::Put, url, Some(payload))
+            .map(|(body, _)| body)
+    }
+
+    /// Perform a GET request to `path` and to each successive `rel="next"`
+    /// URL in the `Link` response headers, and return all of the returned
+    /// items
+    pub(super) fn paginate<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut next = Some(self.mkurl(path)?);
+        while let Some(url) = next {
+            let (page, next_url) = self.request::<(), Vec<T>>(Method::Get, url, None)?;
+            items.extend(page);
+            next = next_url;
+        }
+        Ok(items)
+    }
+
+    fn mkurl(&self, path: &str) -> anyhow::Result<Url> {
+        self.api_url
+            .join(path)
+            .with_context(|| format!("failed to construct an API URL from path {path:?}"))
+    }
+
+    /// Make a request and deserialize the response body (treating an empty
+    /// body as `null`).  Also returns the `rel="next"` URL from the `Link`
+    /// response header, if any.
+    fn request<T: Serialize, U: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: Url,
+        payload: Option<&T>,
+    ) -> anyhow::Result<(U, Option<Url>)> {
+        let mut retries = 0;
+        loop {
+            let req = match method {
+                Method::Get => self.agent.get(url.as_str()).force_send_body(),
+                Method::Post => self.agent.post(url.as_str()),
+                Method::Put => self.agent.put(url.as_str()),
+                Method::Patch => self.agent.patch(url.as_str()),
+                Method::Delete => self.agent.delete(url.as_str()).force_send_body(),
+            }
+            .header("Authorization", &self.auth);
+            debug!("{method} {url}");
+            let resp = if let Some(p) = payload {
+                req.send_json(p)
+            } else {
+                req.send_empty()
+            };
+            let mut resp =
+                resp.with_context(|| format!("failed to make {method} request to {url}"))?;
+            let status = resp.status();
+            debug!("Server returned {status}");
+            if status.as_u16() == 429 && retries < MAX_TOO_MANY_REQUESTS_RETRIES {
+                retries += 1;
+                let delay = resp
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .map_or(TOO_MANY_REQUESTS_WAIT, Duration::from_secs);
+                info!(
+                    "{} returned 429 Too Many Requests; waiting {} seconds before retrying",
+                    url.host_str().unwrap_or("server"),
+                    delay.as_secs()
+                );
+                sleep(delay);
+                continue;
+            }
+            let next = resp
+                .headers()
+                .get("link")
+                .and_then(|v| v.to_str().ok())
+                .and_then(next_link)
+                .and_then(|s| url.join(s).ok());
+            let body = resp.body_mut().read_to_string().with_context(|| {
+                format!("failed to read response body from {method} request to {url}")
+            })?;
+            if !status.is_success() {
+                anyhow::bail!("{method} request to {url} returned {status}: {body}");
+            }
+            let body = if body.trim().is_empty() {
+                "null"
+            } else {
+                &body
+            };
+            let value = serde_json::from_str(body).with_context(|| {
+                format!("failed to deserialize response body from {method} request to {url}")
+            })?;
+            return Ok((value, next));
+        }
+    }
+}
+
+/// Return the URL with `rel="next"` in the value of a `Link` header
+pub(crate) fn next_link(header: &str) -> Option<&str> {
+    header.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|p| p.trim() == r#"rel="next""#)
+            .then(|| target.trim().strip_prefix('<')?.strip_suffix('>'))
+            .flatten()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(
+        concat!(
+            r#"<https://gitlab.example.com/api/v4/projects/1/labels?page=2>; rel="next", "#,
+            r#"<https://gitlab.example.com/api/v4/projects/1/labels?page=5>; rel="last""#,
+        ),
+        Some("https://gitlab.example.com/api/v4/projects/1/labels?page=2")
+    )]
+    #[case(
+        concat!(
+            r#"<https://codeberg.org/api/v1/repos/o/r/labels?page=1>; rel="first", "#,
+            r#"<https://codeberg.org/api/v1/repos/o/r/labels?page=3>; rel="prev""#,
+        ),
+        None
+    )]
+    #[case("", None)]
+    fn test_next_link(#[case] header: &str, #[case] next: Option<&str>) {
+        assert_eq!(next_link(header), next);
+    }
+}
@@ -0,0 +1,217 @@
+use super::client::ApiClient;
+use super::{Forge, PullRequestInfo, RemoteRepo, RepositoryInfo};
+use crate::github::{CreateLabel, CreatePullRequest};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use url::Url;
+
+/// A client for the REST API of a Gitea or Forgejo instance
+#[derive(Clone, Debug)]
+pub(super) struct Gitea(ApiClient);
+
+impl Gitea {
+    /// Create a client for the Gitea instance whose web interface is at
+    /// `base_url`
+    pub(super) fn new(base_url: Url, token: &str) -> anyhow::Result<Gitea> {
+        let api_url = base_url.join("api/v1/")?;
+        Ok(Gitea(ApiClient::new(api_url, token)))
+    }
+
+    fn get_labels(&self, repo: &RemoteRepo) -> anyhow::Result<Vec<Label>> {
+        self.0
+            .paginate::<Label>(&format!("{}/labels?limit=50", repo_path(repo)))
+    }
+}
+
+impl Forge for Gitea {
+    fn get_repository(&self, repo: &RemoteRepo) -> anyhow::Result<RepositoryInfo> {
+        let r = self.0.get::<Repository>(&repo_path(repo))?;
+        Ok(RepositoryInfo {
+            archived: r.archived,
+        })
+    }
+
+    fn create_pull_request(
+        &self,
+        repo: &RemoteRepo,
+        pr: CreatePullRequest<'_>,
+    ) -> anyhow::Result<PullRequestInfo> {
+        let pr = self.0.post::<_, PullRequest>(
+            &format!("{}/pulls", repo_path(repo)),
+            &CreateGiteaPullRequest {
+                title: pr.title,
+                head: pr.head,
+                base: pr.base,
+                body: pr.body,
+            },
+        )?;
+        Ok(PullRequestInfo {
+            number: pr.number,
+            html_url: pr.html_url,
+        })
+    }
+
+    fn get_label_names(&self, repo: &RemoteRepo) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .get_labels(repo)?
+            .into_iter()
+            .map(|lbl| lbl.name)
+            .collect())
+    }
+
+    fn create_label(&self, repo: &RemoteRepo, label: CreateLabel<'_>) -> anyhow::Result<()> {
+        self.0.post::<_, serde::de::IgnoredAny>(
+            &format!("{}/labels", repo_path(repo)),
+            &CreateLabel {
+                color: Cow::from(format!("#{}", label.color)),
+                ..label
+            },
+        )?;
+        Ok(())
+    }
+
+    fn add_labels_to_pr(
+        &self,
+        repo: &RemoteRepo,
+        prnum: u64,
+        labels: &[&str],
+    ) -> anyhow::Result<()> {
+        // Older Gitea versions only accept label IDs here, so look them up
+        let existing = self.get_labels(repo)?;
+        let mut ids = Vec::with_capacity(labels.len());
+        for name in labels {
+            let Some(lbl) = existing
+                .iter()
+                .find(|lbl| lbl.name.eq_ignore_ascii_case(name))
+            else {
+                anyhow::bail!("label {name:?} does not exist in {repo}");
+            };
+            ids.push(lbl.id);
+        }
+        self.0.post::<_, serde::de::IgnoredAny>(
+            &format!("{}/issues/{prnum}/labels", repo_path(repo)),
+            &serde_json::json!({"labels": ids}),
+        )?;
+        Ok(())
+    }
+}
+
+fn repo_path(repo: &RemoteRepo) -> String {
+    format!("repos/{}", repo.path())
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct Repository {
+    archived: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct CreateGiteaPullRequest<'a> {
+    title: Cow<'a, str>,
+    head: Cow<'a, str>,
+    base: Cow<'a, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<Cow<'a, str>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct PullRequest {
+    number: u64,
+    html_url: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct Label {
+    id: u64,
+    name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::mock::{MockServer, Route};
+    use serde_json::json;
+
+    #[test]
+    fn test_submit_pull_request() {
+        let server = MockServer::start(vec![
+            Route {
+                method: "GET",
+                path: "/api/v1/repos/owner/repo",
+                status: 200,
+                body: json!({
+                    "id": 42,
+                    "full_name": "owner/repo",
+                    "archived": true,
+                    "default_branch": "trunk",
+                    "html_url": "https://codeberg.org/owner/repo",
+                }),
+            },
+            Route {
+                method: "POST",
+                path: "/api/v1/repos/owner/repo/pulls",
+                status: 201,
+                body: json!({
+                    "id": 1234,
+                    "number": 3,
+                    "html_url": "https://codeberg.org/owner/repo/pulls/3",
+                }),
+            },
+            Route {
+                method: "GET",
+                path: "/api/v1/repos/owner/repo/labels",
+                status: 200,
+                body: json!([
+                    {"id": 10, "name": "Dependencies", "color": "0052cc"},
+                    {"id": 11, "name": "sweep", "color": "d93f0b"},
+                ]),
+            },
+            Route {
+                method: "POST",
+                path: "/api/v1/repos/owner/repo/issues/3/labels",
+                status: 200,
+                body: json!([]),
+            },
+        ]);
+        let url = server.url();
+        let repo = RemoteRepo::from_url(&format!("{url}owner/repo.git")).unwrap();
+        let gitea = Gitea::new(repo.base_url().unwrap(), "hunter2").unwrap();
+        let info = gitea.get_repository(&repo).unwrap();
+        assert!(info.archived);
+        let pr = gitea
+            .create_pull_request(
+                &repo,
+                CreatePullRequest {
+                    title: Cow::from("Update things"),
+                    head: Cow::from("forall-runpr-20250101000000"),
+                    base: Cow::from("trunk"),
+                    body: Some(Cow::from("Automated changes")),
+                    maintainer_can_modify: true,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            pr,
+            PullRequestInfo {
+                number: 3,
+                html_url: String::from("https://codeberg.org/owner/repo/pulls/3"),
+            }
+        );
+        gitea
+            .add_labels_to_pr(&repo, 3, &["dependencies", "sweep"])
+            .unwrap();
+        assert!(gitea.add_labels_to_pr(&repo, 3, &["nonexistent"]).is_err());
+        let received = server.received();
+        assert_eq!(
+            received[1].body,
+            Some(json!({
+                "title": "Update things",
+                "head": "forall-runpr-20250101000000",
+                "base": "trunk",
+                "body": "Automated changes",
+            }))
+        );
+        assert_eq!(received[3].path, "/api/v1/repos/owner/repo/issues/3/labels");
+        assert_eq!(received[3].body, Some(json!({"labels": [10, 11]})));
+    }
+}
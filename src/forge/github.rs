@@ -0,0 +1,51 @@
+use super::{Forge, PullRequestInfo, RemoteRepo, RepositoryInfo};
+use crate::github::{CreateLabel, CreatePullRequest, GitHub};
+use anyhow::Context;
+use ghrepo::GHRepo;
+
+impl Forge for GitHub {
+    fn get_repository(&self, repo: &RemoteRepo) -> anyhow::Result<RepositoryInfo> {
+        let r = GitHub::get_repository(self, &to_ghrepo(repo)?)?;
+        Ok(RepositoryInfo {
+            archived: r.archived,
+        })
+    }
+
+    fn create_pull_request(
+        &self,
+        repo: &RemoteRepo,
+        pr: CreatePullRequest<'_>,
+    ) -> anyhow::Result<PullRequestInfo> {
+        let pr = GitHub::create_pull_request(self, &to_ghrepo(repo)?, pr)?;
+        Ok(PullRequestInfo {
+            number: pr.number,
+            html_url: pr.html_url,
+        })
+    }
+
+    fn get_label_names(&self, repo: &RemoteRepo) -> anyhow::Result<Vec<String>> {
+        Ok(GitHub::get_labels(self, &to_ghrepo(repo)?)?
+            .into_iter()
+            .map(|li| li.name)
+            .collect())
+    }
+
+    fn create_label(&self, repo: &RemoteRepo, label: CreateLabel<'_>) -> anyhow::Result<()> {
+        GitHub::create_label(self, &to_ghrepo(repo)?, label)
+    }
+
+    fn add_labels_to_pr(
+        &self,
+        repo: &RemoteRepo,
+        prnum: u64,
+        labels: &[&str],
+    ) -> anyhow::Result<()> {
+        GitHub::add_labels_to_pr(self, &to_ghrepo(repo)?, prnum, labels)
+    }
+}
+
+fn to_ghrepo(repo: &RemoteRepo) -> anyhow::Result<GHRepo> {
+    repo.path()
+        .parse::<GHRepo>()
+        .with_context(|| format!("invalid GitHub repository path {:?}", repo.path()))
+}
@@ -0,0 +1,242 @@
+use super::client::ApiClient;
+use super::{Forge, PullRequestInfo, RemoteRepo, RepositoryInfo};
+use crate::github::{CreateLabel, CreatePullRequest, urlencode};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use url::Url;
+
+/// A client for the REST API of a GitLab instance
+#[derive(Clone, Debug)]
+pub(super) struct GitLab(ApiClient);
+
+impl GitLab {
+    /// Create a client for the GitLab instance whose web interface is at
+    /// `base_url`
+    pub(super) fn new(base_url: Url, token: &str) -> anyhow::Result<GitLab> {
+        let api_url = base_url.join("api/v4/")?;
+        Ok(GitLab(ApiClient::new(api_url, token)))
+    }
+}
+
+impl Forge for GitLab {
+    fn get_repository(&self, repo: &RemoteRepo) -> anyhow::Result<RepositoryInfo> {
+        let project = self.0.get::<Project>(&project_path(repo))?;
+        Ok(RepositoryInfo {
+            archived: project.archived,
+        })
+    }
+
+    fn create_pull_request(
+        &self,
+        repo: &RemoteRepo,
+        pr: CreatePullRequest<'_>,
+    ) -> anyhow::Result<PullRequestInfo> {
+        let mr = self.0.post::<_, MergeRequest>(
+            &format!("{}/merge_requests", project_path(repo)),
+            &CreateMergeRequest {
+                source_branch: pr.head,
+                target_branch: pr.base,
+                title: pr.title,
+                description: pr.body,
+            },
+        )?;
+        Ok(PullRequestInfo {
+            number: mr.iid,
+            html_url: mr.web_url,
+        })
+    }
+
+    fn get_label_names(&self, repo: &RemoteRepo) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .0
+            .paginate::<Label>(&format!("{}/labels?per_page=100", project_path(repo)))?
+            .into_iter()
+            .map(|lbl| lbl.name)
+            .collect())
+    }
+
+    fn create_label(&self, repo: &RemoteRepo, label: CreateLabel<'_>) -> anyhow::Result<()> {
+        self.0.post::<_, serde::de::IgnoredAny>(
+            &format!("{}/labels", project_path(repo)),
+            &CreateLabel {
+                color: Cow::from(format!("#{}", label.color)),
+                ..label
+            },
+        )?;
+        Ok(())
+    }
+
+    fn add_labels_to_pr(
+        &self,
+        repo: &RemoteRepo,
+        prnum: u64,
+        labels: &[&str],
+    ) -> anyhow::Result<()> {
+        self.0.put::<_, serde::de::IgnoredAny>(
+            &format!("{}/merge_requests/{prnum}", project_path(repo)),
+            &serde_json::json!({"add_labels": labels.join(",")}),
+        )?;
+        Ok(())
+    }
+}
+
+/// Return the API path for a project, which GitLab identifies by its
+/// URL-encoded full path
+fn project_path(repo: &RemoteRepo) -> String {
+    format!("projects/{}", urlencode(repo.path()))
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct Project {
+    archived: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct CreateMergeRequest<'a> {
+    source_branch: Cow<'a, str>,
+    target_branch: Cow<'a, str>,
+    title: Cow<'a, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<Cow<'a, str>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct MergeRequest {
+    iid: u64,
+    web_url: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct Label {
+    name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::mock::{MockServer, Route};
+    use serde_json::json;
+
+    #[test]
+    fn test_submit_merge_request() {
+        let server = MockServer::start(vec![
+            Route {
+                method: "GET",
+                path: "/api/v4/projects/group%2Fsub%2Fproj",
+                status: 200,
+                body: json!({
+                    "id": 42,
+                    "archived": false,
+                    "default_branch": "main",
+                    "web_url": "https://gitlab.example.com/group/sub/proj",
+                }),
+            },
+            Route {
+                method: "POST",
+                path: "/api/v4/projects/group%2Fsub%2Fproj/merge_requests",
+                status: 201,
+                body: json!({
+                    "id": 1234,
+                    "iid": 7,
+                    "web_url": "https://gitlab.example.com/group/sub/proj/-/merge_requests/7",
+                }),
+            },
+            Route {
+                method: "GET",
+                path: "/api/v4/projects/group%2Fsub%2Fproj/labels",
+                status: 200,
+                body: json!([{"id": 1, "name": "dependencies", "color": "#0052cc"}]),
+            },
+            Route {
+                method: "POST",
+                path: "/api/v4/projects/group%2Fsub%2Fproj/labels",
+                status: 201,
+                body: json!({"id": 2, "name": "sweep", "color": "#d93f0b"}),
+            },
+            Route {
+                method: "PUT",
+                path: "/api/v4/projects/group%2Fsub%2Fproj/merge_requests/7",
+                status: 200,
+                body: json!({"iid": 7}),
+            },
+        ]);
+        let url = server.url();
+        let repo = RemoteRepo::from_url(&format!("{url}group/sub/proj.git")).unwrap();
+        let gitlab = GitLab::new(repo.base_url().unwrap(), "hunter2").unwrap();
+        let info = gitlab.get_repository(&repo).unwrap();
+        assert_eq!(info, RepositoryInfo { archived: false });
+        let pr = gitlab
+            .create_pull_request(
+                &repo,
+                CreatePullRequest {
+                    title: Cow::from("Update things"),
+                    head: Cow::from("forall-runpr-20250101000000"),
+                    base: Cow::from("main"),
+                    body: None,
+                    maintainer_can_modify: true,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            pr,
+            PullRequestInfo {
+                number: 7,
+                html_url: String::from(
+                    "https://gitlab.example.com/group/sub/proj/-/merge_requests/7"
+                ),
+            }
+        );
+        assert_eq!(
+            gitlab.get_label_names(&repo).unwrap(),
+            vec![String::from("dependencies")]
+        );
+        gitlab
+            .create_label(
+                &repo,
+                CreateLabel {
+                    name: Cow::from("sweep"),
+                    color: Cow::from("d93f0b"),
+                    description: None,
+                },
+            )
+            .unwrap();
+        gitlab
+            .add_labels_to_pr(&repo, 7, &["dependencies", "sweep"])
+            .unwrap();
+        let received = server.received();
+        assert_eq!(received.len(), 5);
+        assert!(
+            received
+                .iter()
+                .all(|r| r.authorization.as_deref() == Some("Bearer hunter2"))
+        );
+        assert_eq!(
+            received[1].body,
+            Some(json!({
+                "source_branch": "forall-runpr-20250101000000",
+                "target_branch": "main",
+                "title": "Update things",
+            }))
+        );
+        assert_eq!(
+            received[2].path,
+            "/api/v4/projects/group%2Fsub%2Fproj/labels?per_page=100"
+        );
+        assert_eq!(
+            received[3].body,
+            Some(json!({"name": "sweep", "color": "#d93f0b"}))
+        );
+        assert_eq!(
+            received[4].body,
+            Some(json!({"add_labels": "dependencies,sweep"}))
+        );
+    }
+
+    #[test]
+    fn test_missing_project() {
+        let server = MockServer::start(Vec::new());
+        let repo = RemoteRepo::from_url(&format!("{}owner/repo", server.url())).unwrap();
+        let gitlab = GitLab::new(repo.base_url().unwrap(), "hunter2").unwrap();
+        assert!(gitlab.get_repository(&repo).is_err());
+    }
+}
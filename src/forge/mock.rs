@@ -0,0 +1,143 @@
+//! A minimal HTTP server for testing forge clients
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// A canned response to a request with a given method and path (excluding
+/// the query string)
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Route {
+    pub(super) method: &'static str,
+    pub(super) path: &'static str,
+    pub(super) status: u16,
+    pub(super) body: serde_json::Value,
+}
+
+/// A request received by a `MockServer`
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct ReceivedRequest {
+    pub(super) method: String,
+    /// The request path, including the query string
+    pub(super) path: String,
+    pub(super) authorization: Option<String>,
+    pub(super) body: Option<serde_json::Value>,
+}
+
+/// A server running in a background thread that responds to requests
+/// according to a fixed list of routes.  Requests that do not match any route
+/// receive a 404 response.
+#[derive(Debug)]
+pub(super) struct MockServer {
+    url: Url,
+    received: Arc<Mutex<Vec<ReceivedRequest>>>,
+}
+
+impl MockServer {
+    pub(super) fn start(routes: Vec<Route>) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("binding to localhost should work");
+        let addr = listener
+            .local_addr()
+            .expect("listener should have a local address");
+        let url = Url::parse(&format!("http://{addr}")).expect("server URL should be valid");
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&received);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    break;
+                };
+                if handle(stream, &routes, &log).is_err() {
+                    break;
+                }
+            }
+        });
+        MockServer { url, received }
+    }
+
+    pub(super) fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    /// Return the requests received so far
+    pub(super) fn received(&self) -> Vec<ReceivedRequest> {
+        self.received
+            .lock()
+            .expect("received request log should not be poisoned")
+            .clone()
+    }
+}
+
+fn handle(
+    stream: TcpStream,
+    routes: &[Route],
+    log: &Mutex<Vec<ReceivedRequest>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let path = parts.next().unwrap_or_default().to_owned();
+    let mut content_length = 0;
+    let mut authorization = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse::<usize>().unwrap_or_default();
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.to_owned());
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body = (!body.is_empty())
+        .then(|| serde_json::from_slice::<serde_json::Value>(&body).ok())
+        .flatten();
+    let route_path = path.split_once('?').map_or(path.as_str(), |(p, _)| p);
+    let (status, payload) = routes
+        .iter()
+        .find(|r| r.method == method && r.path == route_path)
+        .map_or_else(
+            || (404, String::from(r#"{"message":"Not Found"}"#)),
+            |r| (r.status, r.body.to_string()),
+        );
+    log.lock()
+        .expect("received request log should not be poisoned")
+        .push(ReceivedRequest {
+            method,
+            path,
+            authorization,
+            body,
+        });
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        concat!(
+            "HTTP/1.1 {status} {reason}\r\n",
+            "Content-Type: application/json\r\n",
+            "Content-Length: {len}\r\n",
+            "Connection: close\r\n",
+            "\r\n",
+            "{payload}",
+        ),
+        reason = match status {
+            200 => "OK",
+            201 => "Created",
+            404 => "Not Found",
+            _ => "Unknown",
+        },
+        len = payload.len(),
+        status = status,
+        payload = payload,
+    )?;
+    stream.flush()
+}
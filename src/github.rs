@@ -1,10 +1,18 @@
+use crate::forge::next_link;
 use anyhow::Context;
 use ghrepo::GHRepo;
 use itertools::Itertools;
-use serde::{Deserialize, Serialize};
+use minigh::{Method, RequestError};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use url::Url;
 
-static USER_AGENT: &str = concat!(
+/// The base URL for the GitHub REST API
+static API_URL: &str = "https://api.github.com";
+
+pub(crate) static USER_AGENT: &str = concat!(
     env!("CARGO_PKG_NAME"),
     "/",
     env!("CARGO_PKG_VERSION"),
@@ -31,11 +39,162 @@ impl GitHub {
         GitHub::new(&token).map_err(Into::into)
     }
 
+    /// Make a request via `minigh` (which waits and retries when rate limits
+    /// are hit), log the remaining rate limit quota, and deserialize the
+    /// response body
+    fn request<T: Serialize, U: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        payload: Option<&T>,
+    ) -> Result<U, RequestError> {
+        let url = mkurl(path)?;
+        self.request_url(method, url, payload).map(|(body, _)| body)
+    }
+
+    /// Make a request to `url` and deserialize the response body.  Also
+    /// returns the `rel="next"` URL from the `Link` response header, if any.
+    fn request_url<T: Serialize, U: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: Url,
+        payload: Option<&T>,
+    ) -> Result<(U, Option<Url>), RequestError> {
+        let mut r = self.0.request(method, url.clone(), payload)?;
+        log_ratelimit(&r);
+        let next = r
+            .headers()
+            .get("link")
+            .and_then(|v| v.to_str().ok())
+            .and_then(next_link)
+            .and_then(|s| url.join(s).ok());
+        match r.body_mut().read_json::<U>() {
+            Ok(body) => Ok((body, next)),
+            Err(source) => Err(RequestError::Deserialize {
+                method,
+                url,
+                source: Box::new(source),
+            }),
+        }
+    }
+
+    fn get<U: DeserializeOwned>(&self, path: &str) -> Result<U, RequestError> {
+        self.request::<(), U>(Method::Get, path, None)
+    }
+
+    fn post<T: Serialize, U: DeserializeOwned>(
+        &self,
+        path: &str,
+        payload: &T,
+    ) -> Result<U, RequestError> {
+        self.request(Method::Post, path, Some(payload))
+    }
+
+    fn put<T: Serialize, U: DeserializeOwned>(
+        &self,
+        path: &str,
+        payload: &T,
+    ) -> Result<U, RequestError> {
+        self.request(Method::Put, path, Some(payload))
+    }
+
+    fn patch<T: Serialize, U: DeserializeOwned>(
+        &self,
+        path: &str,
+        payload: &T,
+    ) -> Result<U, RequestError> {
+        self.request(Method::Patch, path, Some(payload))
+    }
+
+    fn delete(&self, path: &str) -> Result<(), RequestError> {
+        let url = mkurl(path)?;
+        let r = self.0.request::<()>(Method::Delete, url, None)?;
+        log_ratelimit(&r);
+        Ok(())
+    }
+
+    /// Return an iterator that makes a paginated series of GET requests,
+    /// starting with `path` and following the `rel="next"` links, and yields
+    /// the items in each page.  Pages may be either arrays of items or maps
+    /// containing a single array of items.
+    fn paginate<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> impl Iterator<Item = Result<T, RequestError>> + use<'_, T> {
+        let mut next = Some(mkurl(path));
+        let mut items = Vec::new().into_iter();
+        std::iter::from_fn(move || {
+            loop {
+                if let Some(item) = items.next() {
+                    return Some(Ok(item));
+                }
+                let url = match next.take()? {
+                    Ok(url) => url,
+                    Err(e) => return Some(Err(e)),
+                };
+                match self.request_url::<(), Page<T>>(Method::Get, url.clone(), None) {
+                    Ok((page, next_url)) => match page.into_items() {
+                        Ok(page_items) => {
+                            items = page_items.into_iter();
+                            next = next_url.map(Ok);
+                        }
+                        Err(source) => {
+                            return Some(Err(RequestError::Deserialize {
+                                method: Method::Get,
+                                url,
+                                source: Box::new(ureq::Error::Json(source)),
+                            }));
+                        }
+                    },
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+        })
+    }
+
     pub(crate) fn get_repository<R>(&self, repo: &R) -> anyhow::Result<Repository>
     where
         for<'a> R: RepositoryEndpoint<'a>,
     {
-        self.0.get(repo.api_url().as_ref()).map_err(Into::into)
+        self.get(repo.api_url().as_ref()).map_err(Into::into)
+    }
+
+    pub(crate) fn update_repository<R>(
+        &self,
+        repo: &R,
+        update: &UpdateRepository,
+    ) -> anyhow::Result<Repository>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.patch(repo.api_url().as_ref(), update)
+            .map_err(Into::into)
+    }
+
+    /// List the repositories belonging to the given organization
+    pub(crate) fn list_org_repositories(&self, org: &str) -> anyhow::Result<Vec<Repository>> {
+        self.paginate::<Repository>(&format!("/orgs/{org}/repos?type=all&per_page=100"))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// List the repositories owned by the given user
+    pub(crate) fn list_user_repositories(&self, user: &str) -> anyhow::Result<Vec<Repository>> {
+        self.paginate::<Repository>(&format!("/users/{user}/repos?type=owner&per_page=100"))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// List the repositories matching the given [search query][1]
+    ///
+    /// [1]: https://docs.github.com/en/search-github/searching-on-github/searching-for-repositories
+    pub(crate) fn search_repositories(&self, query: &str) -> anyhow::Result<Vec<Repository>> {
+        self.paginate::<Repository>(&format!(
+            "/search/repositories?q={}&per_page=100",
+            urlencode(query)
+        ))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Into::into)
     }
 
     pub(crate) fn create_pull_request<R>(
@@ -46,18 +205,237 @@ impl GitHub {
     where
         for<'a> R: RepositoryEndpoint<'a>,
     {
-        self.0
-            .post(&format!("{}/pulls", repo.api_url().as_ref()), &pr)
+        self.post(&format!("{}/pulls", repo.api_url().as_ref()), &pr)
             .map_err(Into::into)
     }
 
-    pub(crate) fn get_label_names<R>(&self, repo: &R) -> anyhow::Result<Vec<String>>
+    pub(crate) fn get_pull_request<R>(&self, repo: &R, prnum: u64) -> anyhow::Result<PullRequest>
     where
         for<'a> R: RepositoryEndpoint<'a>,
     {
-        self.0
-            .paginate::<LabelInfo>(&format!("{}/labels", repo.api_url().as_ref()))
-            .map_ok(|li| li.name)
+        self.get(&format!("{}/pulls/{prnum}", repo.api_url().as_ref()))
+            .map_err(Into::into)
+    }
+
+    pub(crate) fn list_open_pull_requests<R>(&self, repo: &R) -> anyhow::Result<Vec<PullRequest>>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.paginate::<PullRequest>(&format!("{}/pulls?state=open", repo.api_url().as_ref()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    pub(crate) fn merge_pull_request<R>(
+        &self,
+        repo: &R,
+        prnum: u64,
+        method: MergeMethod,
+    ) -> anyhow::Result<()>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.put::<_, serde::de::IgnoredAny>(
+            &format!("{}/pulls/{prnum}/merge", repo.api_url().as_ref()),
+            &MergePullRequest {
+                merge_method: method,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn close_pull_request<R>(&self, repo: &R, prnum: u64) -> anyhow::Result<()>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.patch::<_, serde::de::IgnoredAny>(
+            &format!("{}/pulls/{prnum}", repo.api_url().as_ref()),
+            &serde_json::json!({"state": "closed"}),
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_branch_names<R>(&self, repo: &R) -> anyhow::Result<Vec<String>>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.paginate::<BranchInfo>(&format!("{}/branches", repo.api_url().as_ref()))
+            .map_ok(|bi| bi.name)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    pub(crate) fn delete_branch<R>(&self, repo: &R, branch: &str) -> anyhow::Result<()>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.delete(&format!(
+            "{}/git/refs/heads/{}",
+            repo.api_url().as_ref(),
+            urlencode_ref(branch)
+        ))
+        .map_err(Into::into)
+    }
+
+    /// Rename a branch in a repository.  If the branch is the default branch,
+    /// the default branch is changed to the new name.
+    pub(crate) fn rename_branch<R>(
+        &self,
+        repo: &R,
+        branch: &str,
+        new_name: &str,
+    ) -> anyhow::Result<()>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.post::<_, serde::de::IgnoredAny>(
+            &format!(
+                "{}/branches/{}/rename",
+                repo.api_url().as_ref(),
+                urlencode_ref(branch)
+            ),
+            &serde_json::json!({"new_name": new_name}),
+        )?;
+        Ok(())
+    }
+
+    /// Return the branch protection settings for a branch, or `None` if the
+    /// branch is not protected
+    pub(crate) fn get_branch_protection<R>(
+        &self,
+        repo: &R,
+        branch: &str,
+    ) -> anyhow::Result<Option<BranchProtection>>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        match self.get(&format!(
+            "{}/branches/{}/protection",
+            repo.api_url().as_ref(),
+            urlencode_ref(branch)
+        )) {
+            Ok(bp) => Ok(Some(bp)),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub(crate) fn set_branch_protection<R>(
+        &self,
+        repo: &R,
+        branch: &str,
+        protection: &SetBranchProtection,
+    ) -> anyhow::Result<()>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.put::<_, serde::de::IgnoredAny>(
+            &format!(
+                "{}/branches/{}/protection",
+                repo.api_url().as_ref(),
+                urlencode_ref(branch)
+            ),
+            protection,
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_review_status<R>(&self, repo: &R, prnum: u64) -> anyhow::Result<ReviewStatus>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        let reviews = self
+            .paginate::<Review>(&format!(
+                "{}/pulls/{prnum}/reviews",
+                repo.api_url().as_ref()
+            ))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ReviewStatus::from_reviews(&reviews))
+    }
+
+    /// Determine the combined status of all commit statuses and check runs
+    /// for the given ref
+    pub(crate) fn get_ci_status<R>(&self, repo: &R, gitref: &str) -> anyhow::Result<CiStatus>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        let gitref = urlencode_ref(gitref);
+        let combined = self.get::<CombinedStatus>(&format!(
+            "{}/commits/{gitref}/status",
+            repo.api_url().as_ref()
+        ))?;
+        let mut status = if combined.total_count == 0 {
+            CiStatus::None
+        } else {
+            match combined.state.as_str() {
+                "success" => CiStatus::Success,
+                "pending" => CiStatus::Pending,
+                _ => CiStatus::Failure,
+            }
+        };
+        for run in self.paginate::<CheckRun>(&format!(
+            "{}/commits/{gitref}/check-runs",
+            repo.api_url().as_ref()
+        )) {
+            status = status.combine(run?.ci_status());
+        }
+        Ok(status)
+    }
+
+    pub(crate) fn create_release<R>(
+        &self,
+        repo: &R,
+        release: CreateRelease<'_>,
+    ) -> anyhow::Result<Release>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.post(&format!("{}/releases", repo.api_url().as_ref()), &release)
+            .map_err(Into::into)
+    }
+
+    /// Return the most recent non-draft, non-prerelease release, or `None` if
+    /// there are no releases
+    pub(crate) fn get_latest_release<R>(&self, repo: &R) -> anyhow::Result<Option<Release>>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        match self.get(&format!("{}/releases/latest", repo.api_url().as_ref())) {
+            Ok(r) => Ok(Some(r)),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Return the release (possibly a draft) for the given tag, or `None` if
+    /// there is no such release
+    pub(crate) fn get_release_for_tag<R>(
+        &self,
+        repo: &R,
+        tag: &str,
+    ) -> anyhow::Result<Option<Release>>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        // The `releases/tags/{tag}` endpoint does not return drafts, so we
+        // have to search the list of releases instead.
+        for r in self.paginate::<Release>(&format!(
+            "{}/releases?per_page=100",
+            repo.api_url().as_ref()
+        )) {
+            let r = r?;
+            if r.tag_name == tag {
+                return Ok(Some(r));
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn get_labels<R>(&self, repo: &R) -> anyhow::Result<Vec<LabelInfo>>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.paginate::<LabelInfo>(&format!("{}/labels?per_page=100", repo.api_url().as_ref()))
             .collect::<Result<Vec<_>, _>>()
             .map_err(Into::into)
     }
@@ -66,13 +444,42 @@ impl GitHub {
     where
         for<'a> R: RepositoryEndpoint<'a>,
     {
-        self.0.post::<_, serde::de::IgnoredAny>(
+        self.post::<_, serde::de::IgnoredAny>(
             &format!("{}/labels", repo.api_url().as_ref()),
             &label,
         )?;
         Ok(())
     }
 
+    /// Update the label currently named `name`
+    pub(crate) fn update_label<R>(
+        &self,
+        repo: &R,
+        name: &str,
+        update: UpdateLabel<'_>,
+    ) -> anyhow::Result<()>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.patch::<_, serde::de::IgnoredAny>(
+            &format!("{}/labels/{}", repo.api_url().as_ref(), urlencode(name)),
+            &update,
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn delete_label<R>(&self, repo: &R, name: &str) -> anyhow::Result<()>
+    where
+        for<'a> R: RepositoryEndpoint<'a>,
+    {
+        self.delete(&format!(
+            "{}/labels/{}",
+            repo.api_url().as_ref(),
+            urlencode(name)
+        ))
+        .map_err(Into::into)
+    }
+
     pub(crate) fn add_labels_to_pr<R>(
         &self,
         repo: &R,
@@ -82,7 +489,7 @@ impl GitHub {
     where
         for<'a> R: RepositoryEndpoint<'a>,
     {
-        self.0.post::<_, serde::de::IgnoredAny>(
+        self.post::<_, serde::de::IgnoredAny>(
             &format!("{}/issues/{prnum}/labels", repo.api_url().as_ref()),
             &labels,
         )?;
@@ -104,13 +511,348 @@ pub(crate) struct Repository {
     pub(crate) url: String,
     pub(crate) private: bool,
     pub(crate) archived: bool,
-    //pub(crate) html_url: String,
+    pub(crate) fork: bool,
+    #[serde(default)]
+    pub(crate) visibility: Option<Visibility>,
+    #[serde(default)]
+    pub(crate) topics: Vec<String>,
+    pub(crate) html_url: String,
+    /// The number of open issues plus the number of open pull requests
+    #[serde(default)]
+    pub(crate) open_issues_count: u64,
+    pub(crate) ssh_url: String,
+    pub(crate) clone_url: String,
+    pub(crate) default_branch: String,
+    #[serde(default)]
+    pub(crate) has_issues: bool,
+    #[serde(default)]
+    pub(crate) has_wiki: bool,
+    // The following fields are only present in responses for individual
+    // repositories when the authenticated user has admin access:
+    #[serde(default)]
+    pub(crate) allow_merge_commit: Option<bool>,
+    #[serde(default)]
+    pub(crate) allow_squash_merge: Option<bool>,
+    #[serde(default)]
+    pub(crate) allow_rebase_merge: Option<bool>,
+    #[serde(default)]
+    pub(crate) delete_branch_on_merge: Option<bool>,
     //pub(crate) description: String,
-    //pub(crate) ssh_url: String,
-    //pub(crate) topics: Vec<String>,
     // owner?
 }
 
+impl Repository {
+    pub(crate) fn visibility(&self) -> Visibility {
+        self.visibility.unwrap_or(if self.private {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        })
+    }
+
+    pub(crate) fn has_topic(&self, topic: &str) -> bool {
+        self.topics.iter().any(|t| t.eq_ignore_ascii_case(topic))
+    }
+}
+
+/// Payload for updating a repository's settings; unset fields are left
+/// unchanged
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub(crate) struct UpdateRepository {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) default_branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) allow_merge_commit: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) allow_squash_merge: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) allow_rebase_merge: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) delete_branch_on_merge: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) has_issues: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) has_wiki: Option<bool>,
+}
+
+impl UpdateRepository {
+    pub(crate) fn is_empty(&self) -> bool {
+        self == &UpdateRepository::default()
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub(crate) struct BranchProtection {
+    #[serde(default)]
+    pub(crate) required_status_checks: Option<RequiredStatusChecks>,
+    #[serde(default)]
+    pub(crate) enforce_admins: Option<Enabled>,
+    #[serde(default)]
+    pub(crate) required_pull_request_reviews: Option<RequiredReviews>,
+    /// Restrictions on who can push; only available for repositories owned
+    /// by organizations
+    #[serde(default)]
+    pub(crate) restrictions: Option<Actors>,
+    #[serde(default)]
+    pub(crate) required_linear_history: Option<Enabled>,
+    #[serde(default)]
+    pub(crate) allow_force_pushes: Option<Enabled>,
+    #[serde(default)]
+    pub(crate) allow_deletions: Option<Enabled>,
+    #[serde(default)]
+    pub(crate) block_creations: Option<Enabled>,
+    #[serde(default)]
+    pub(crate) required_conversation_resolution: Option<Enabled>,
+    #[serde(default)]
+    pub(crate) lock_branch: Option<Enabled>,
+    #[serde(default)]
+    pub(crate) allow_fork_syncing: Option<Enabled>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub(crate) struct RequiredStatusChecks {
+    pub(crate) strict: bool,
+    #[serde(default)]
+    pub(crate) contexts: Vec<String>,
+    #[serde(default)]
+    pub(crate) checks: Vec<StatusCheck>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct StatusCheck {
+    pub(crate) context: String,
+    /// The ID of the app that must provide the check; `None` means that any
+    /// app may provide it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) app_id: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub(crate) struct Enabled {
+    pub(crate) enabled: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub(crate) struct RequiredReviews {
+    #[serde(default)]
+    pub(crate) required_approving_review_count: u32,
+    #[serde(default)]
+    pub(crate) dismiss_stale_reviews: bool,
+    #[serde(default)]
+    pub(crate) require_code_owner_reviews: bool,
+    #[serde(default)]
+    pub(crate) require_last_push_approval: bool,
+    #[serde(default)]
+    pub(crate) dismissal_restrictions: Option<Actors>,
+    #[serde(default)]
+    pub(crate) bypass_pull_request_allowances: Option<Actors>,
+}
+
+/// The users, teams, and apps to which a branch protection permission is
+/// granted
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub(crate) struct Actors {
+    #[serde(default)]
+    pub(crate) users: Vec<User>,
+    #[serde(default)]
+    pub(crate) teams: Vec<Slug>,
+    #[serde(default)]
+    pub(crate) apps: Vec<Slug>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub(crate) struct Slug {
+    pub(crate) slug: String,
+}
+
+/// Payload for replacing a branch's protection settings.  `None` fields are
+/// serialized as `null`, disabling the respective protections, except for the
+/// simple toggles, which are omitted and thus left at their defaults.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub(crate) struct SetBranchProtection {
+    pub(crate) required_status_checks: Option<SetStatusChecks>,
+    pub(crate) enforce_admins: Option<bool>,
+    pub(crate) required_pull_request_reviews: Option<SetRequiredReviews>,
+    pub(crate) restrictions: Option<SetActors>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) required_linear_history: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) allow_force_pushes: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) allow_deletions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) block_creations: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) required_conversation_resolution: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) lock_branch: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) allow_fork_syncing: Option<bool>,
+}
+
+impl From<&BranchProtection> for SetBranchProtection {
+    /// Convert existing protection settings into a payload that will leave
+    /// them unchanged
+    fn from(bp: &BranchProtection) -> SetBranchProtection {
+        let enabled = |e: Option<Enabled>| e.map(|e| e.enabled);
+        SetBranchProtection {
+            required_status_checks: bp
+                .required_status_checks
+                .as_ref()
+                .map(SetStatusChecks::from),
+            enforce_admins: enabled(bp.enforce_admins),
+            required_pull_request_reviews: bp
+                .required_pull_request_reviews
+                .as_ref()
+                .map(SetRequiredReviews::from),
+            restrictions: bp.restrictions.as_ref().map(SetActors::from),
+            required_linear_history: enabled(bp.required_linear_history),
+            allow_force_pushes: enabled(bp.allow_force_pushes),
+            allow_deletions: enabled(bp.allow_deletions),
+            block_creations: enabled(bp.block_creations),
+            required_conversation_resolution: enabled(bp.required_conversation_resolution),
+            lock_branch: enabled(bp.lock_branch),
+            allow_fork_syncing: enabled(bp.allow_fork_syncing),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub(crate) struct SetStatusChecks {
+    pub(crate) strict: bool,
+    pub(crate) checks: Vec<StatusCheck>,
+}
+
+impl From<&RequiredStatusChecks> for SetStatusChecks {
+    fn from(rsc: &RequiredStatusChecks) -> SetStatusChecks {
+        let checks = if rsc.checks.is_empty() {
+            rsc.contexts
+                .iter()
+                .map(|context| StatusCheck {
+                    context: context.clone(),
+                    app_id: None,
+                })
+                .collect()
+        } else {
+            rsc.checks.clone()
+        };
+        SetStatusChecks {
+            strict: rsc.strict,
+            checks,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub(crate) struct SetRequiredReviews {
+    pub(crate) required_approving_review_count: u32,
+    pub(crate) dismiss_stale_reviews: bool,
+    pub(crate) require_code_owner_reviews: bool,
+    pub(crate) require_last_push_approval: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) dismissal_restrictions: Option<SetActors>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) bypass_pull_request_allowances: Option<SetActors>,
+}
+
+impl From<&RequiredReviews> for SetRequiredReviews {
+    fn from(rr: &RequiredReviews) -> SetRequiredReviews {
+        SetRequiredReviews {
+            required_approving_review_count: rr.required_approving_review_count,
+            dismiss_stale_reviews: rr.dismiss_stale_reviews,
+            require_code_owner_reviews: rr.require_code_owner_reviews,
+            require_last_push_approval: rr.require_last_push_approval,
+            dismissal_restrictions: rr.dismissal_restrictions.as_ref().map(SetActors::from),
+            bypass_pull_request_allowances: rr
+                .bypass_pull_request_allowances
+                .as_ref()
+                .map(SetActors::from),
+        }
+    }
+}
+
+/// [`Actors`] as specified when setting branch protection: as lists of user
+/// logins and team & app slugs
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub(crate) struct SetActors {
+    pub(crate) users: Vec<String>,
+    pub(crate) teams: Vec<String>,
+    pub(crate) apps: Vec<String>,
+}
+
+impl From<&Actors> for SetActors {
+    fn from(actors: &Actors) -> SetActors {
+        SetActors {
+            users: actors.users.iter().map(|u| u.login.clone()).collect(),
+            teams: actors.teams.iter().map(|t| t.slug.clone()).collect(),
+            apps: actors.apps.iter().map(|a| a.slug.clone()).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Visibility {
+    Public,
+    Private,
+    Internal,
+}
+
+/// A cache of [`Repository`] details, fetched from the API as needed
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RepoCache {
+    /// The client to use for fetching repositories; if `None`, an
+    /// authenticated client is created when first needed
+    github: Option<GitHub>,
+
+    /// Cached repository details, keyed by lowercased `{owner}/{name}`
+    repos: HashMap<String, Repository>,
+}
+
+impl RepoCache {
+    pub(crate) fn new() -> RepoCache {
+        RepoCache::default()
+    }
+
+    pub(crate) fn with_client(github: GitHub) -> RepoCache {
+        RepoCache {
+            github: Some(github),
+            ..RepoCache::default()
+        }
+    }
+
+    pub(crate) fn insert(&mut self, repo: Repository) {
+        self.repos.insert(repo.full_name.to_ascii_lowercase(), repo);
+    }
+
+    pub(crate) fn get(&mut self, ghrepo: &GHRepo) -> anyhow::Result<&Repository> {
+        let key = ghrepo.to_string().to_ascii_lowercase();
+        if !self.repos.contains_key(&key) {
+            // Insert using `key` rather than the returned `full_name` in case
+            // the repository has been renamed
+            let repo = self.client()?.get_repository(ghrepo)?;
+            self.repos.insert(key.clone(), repo);
+        }
+        Ok(self
+            .repos
+            .get(&key)
+            .expect("Repository should have just been cached"))
+    }
+
+    /// Return the CI status of the head of the repository's default branch
+    pub(crate) fn default_branch_ci_status(&mut self, ghrepo: &GHRepo) -> anyhow::Result<CiStatus> {
+        let branch = self.get(ghrepo)?.default_branch.clone();
+        self.client()?.get_ci_status(ghrepo, &branch)
+    }
+
+    fn client(&mut self) -> anyhow::Result<&GitHub> {
+        match self.github {
+            Some(ref github) => Ok(github),
+            None => Ok(self.github.insert(GitHub::authed()?)),
+        }
+    }
+}
+
 impl<'a> RepositoryEndpoint<'a> for Repository {
     type Url = &'a str;
 
@@ -142,17 +884,216 @@ pub(crate) struct PullRequest {
     pub(crate) url: String,
     pub(crate) html_url: String,
     pub(crate) number: u64,
+    pub(crate) state: String,
+    #[serde(default)]
+    pub(crate) draft: bool,
+    /// Creation timestamp in ISO 8601 format
+    pub(crate) created_at: String,
+    pub(crate) head: PullRequestHead,
+    #[serde(default)]
+    pub(crate) labels: Vec<LabelInfo>,
+    // The following fields are only present in responses for individual PRs:
+    #[serde(default)]
+    pub(crate) merged: bool,
+    #[serde(default)]
+    pub(crate) mergeable: Option<bool>,
+    #[serde(default)]
+    pub(crate) mergeable_state: Option<String>,
     //pub(crate) title: String,
     //#[serde(default)]
     //pub(crate) body: Option<String>,
-    //labels
+}
+
+impl PullRequest {
+    /// Return "merged" for merged PRs, "draft" for open draft PRs, and the
+    /// PR's state otherwise
+    pub(crate) fn display_state(&self) -> &str {
+        if self.merged {
+            "merged"
+        } else if self.draft && self.state == "open" {
+            "draft"
+        } else {
+            &self.state
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub(crate) struct PullRequestHead {
+    #[serde(rename = "ref")]
+    pub(crate) gitref: String,
+    pub(crate) sha: String,
+    /// The repository containing the head branch; `None` if the repository
+    /// has been deleted
+    pub(crate) repo: Option<HeadRepository>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub(crate) struct HeadRepository {
+    pub(crate) full_name: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum MergeMethod {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+struct MergePullRequest {
+    merge_method: MergeMethod,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-struct LabelInfo {
+struct BranchInfo {
     name: String,
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct Review {
+    state: String,
+    user: Option<User>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub(crate) struct User {
+    pub(crate) login: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ReviewStatus {
+    Approved,
+    ChangesRequested,
+    None,
+}
+
+impl ReviewStatus {
+    /// Determine the overall review status of a PR from its reviews, in
+    /// chronological order, by considering only the most recent approval or
+    /// change request from each reviewer
+    fn from_reviews(reviews: &[Review]) -> ReviewStatus {
+        let mut latest = HashMap::new();
+        for r in reviews {
+            if matches!(
+                r.state.as_str(),
+                "APPROVED" | "CHANGES_REQUESTED" | "DISMISSED"
+            ) {
+                let login = r.user.as_ref().map(|u| u.login.as_str());
+                latest.insert(login, r.state.as_str());
+            }
+        }
+        if latest.values().any(|&st| st == "CHANGES_REQUESTED") {
+            ReviewStatus::ChangesRequested
+        } else if latest.values().any(|&st| st == "APPROVED") {
+            ReviewStatus::Approved
+        } else {
+            ReviewStatus::None
+        }
+    }
+}
+
+impl fmt::Display for ReviewStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewStatus::Approved => write!(f, "approved"),
+            ReviewStatus::ChangesRequested => write!(f, "changes requested"),
+            ReviewStatus::None => write!(f, "none"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct CombinedStatus {
+    state: String,
+    total_count: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct CheckRun {
+    status: String,
+    conclusion: Option<String>,
+}
+
+impl CheckRun {
+    fn ci_status(&self) -> CiStatus {
+        if self.status != "completed" {
+            CiStatus::Pending
+        } else {
+            match self.conclusion.as_deref() {
+                Some("success" | "neutral" | "skipped") => CiStatus::Success,
+                _ => CiStatus::Failure,
+            }
+        }
+    }
+}
+
+/// The overall state of the CI checks for a commit
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CiStatus {
+    /// No statuses or check runs were reported for the commit
+    None,
+    Success,
+    Pending,
+    Failure,
+}
+
+impl CiStatus {
+    /// Combine two statuses, with failures taking precedence over pending
+    /// checks, which take precedence over successes
+    fn combine(self, other: CiStatus) -> CiStatus {
+        self.max(other)
+    }
+}
+
+impl fmt::Display for CiStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CiStatus::None => write!(f, "none"),
+            CiStatus::Success => write!(f, "pass"),
+            CiStatus::Pending => write!(f, "pending"),
+            CiStatus::Failure => write!(f, "fail"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct CreateRelease<'a> {
+    pub(crate) tag_name: Cow<'a, str>,
+    pub(crate) name: Cow<'a, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) body: Option<Cow<'a, str>>,
+    pub(crate) draft: bool,
+    pub(crate) prerelease: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub(crate) struct Release {
+    pub(crate) tag_name: String,
+    pub(crate) html_url: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub(crate) struct LabelInfo {
+    pub(crate) name: String,
+    /// Six-digit hex RGB color without a leading `#`
+    pub(crate) color: String,
+    pub(crate) description: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct UpdateLabel<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) new_name: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) color: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<Cow<'a, str>>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub(crate) struct CreateLabel<'a> {
     pub(crate) name: Cow<'a, str>,
@@ -160,3 +1101,154 @@ pub(crate) struct CreateLabel<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) description: Option<Cow<'a, str>>,
 }
+
+/// A page of results from a paginated GitHub API endpoint: either an array of
+/// items or an object containing a single array of items (as returned by,
+/// e.g., the search and check runs endpoints)
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum Page<T> {
+    Array(Vec<T>),
+    Map(HashMap<String, serde_json::Value>),
+}
+
+impl<T: DeserializeOwned> Page<T> {
+    fn into_items(self) -> Result<Vec<T>, serde_json::Error> {
+        match self {
+            Page::Array(items) => Ok(items),
+            Page::Map(map) => {
+                let mut lists = map.into_values().filter(serde_json::Value::is_array);
+                match (lists.next(), lists.next()) {
+                    (Some(items), None) => serde_json::from_value(items),
+                    _ => Err(serde::de::Error::custom(
+                        "expected page object to contain exactly one array field",
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// Construct a GitHub API URL from a path relative to the API root
+fn mkurl(path: &str) -> Result<Url, RequestError> {
+    Url::parse(API_URL)
+        .and_then(|base| base.join(path))
+        .map_err(|source| RequestError::Path {
+            source,
+            path: path.to_owned(),
+        })
+}
+
+/// Log the remaining GitHub API rate limit quota reported in a response's
+/// headers
+fn log_ratelimit(r: &ureq::http::Response<ureq::Body>) {
+    let header = |name: &str| r.headers().get(name).and_then(|v| v.to_str().ok());
+    if let (Some(remaining), Some(reset)) =
+        (header("x-ratelimit-remaining"), header("x-ratelimit-reset"))
+    {
+        debug!("GitHub API rate limit: {remaining} requests remaining; resets at {reset}");
+    }
+}
+
+/// Test whether an error returned by a `minigh` request was caused by a 404
+/// response
+fn is_not_found(e: &RequestError) -> bool {
+    matches!(e, RequestError::Status(st) if st.status.as_u16() == 404)
+}
+
+/// Percent-encode a string for use as a URL query parameter value or path
+/// segment
+pub(crate) fn urlencode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            encoded.push(char::from(b));
+        } else {
+            let _ = write!(encoded, "%{b:02X}");
+        }
+    }
+    encoded
+}
+
+/// Percent-encode each `/`-separated segment of a Git ref name (e.g., a
+/// branch name) for use in a URL path, leaving the slashes as-is
+fn urlencode_ref(s: &str) -> String {
+    s.split('/').map(urlencode).join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn review(login: &str, state: &str) -> Review {
+        Review {
+            state: state.to_owned(),
+            user: Some(User {
+                login: login.to_owned(),
+            }),
+        }
+    }
+
+    #[rstest]
+    #[case(Vec::new(), ReviewStatus::None)]
+    #[case(vec![review("alice", "COMMENTED")], ReviewStatus::None)]
+    #[case(vec![review("alice", "APPROVED")], ReviewStatus::Approved)]
+    #[case(
+        vec![review("alice", "APPROVED"), review("bob", "CHANGES_REQUESTED")],
+        ReviewStatus::ChangesRequested
+    )]
+    #[case(
+        vec![review("alice", "CHANGES_REQUESTED"), review("alice", "APPROVED")],
+        ReviewStatus::Approved
+    )]
+    #[case(
+        vec![review("alice", "APPROVED"), review("alice", "COMMENTED")],
+        ReviewStatus::Approved
+    )]
+    #[case(
+        vec![review("alice", "APPROVED"), review("alice", "DISMISSED")],
+        ReviewStatus::None
+    )]
+    fn test_review_status(#[case] reviews: Vec<Review>, #[case] status: ReviewStatus) {
+        assert_eq!(ReviewStatus::from_reviews(&reviews), status);
+    }
+
+    #[rstest]
+    #[case("rust", "rust")]
+    #[case("topic:cli user:octocat", "topic%3Acli%20user%3Aoctocat")]
+    #[case("a+b&c=d", "a%2Bb%26c%3Dd")]
+    #[case("naïve", "na%C3%AFve")]
+    fn test_urlencode(#[case] s: &str, #[case] encoded: &str) {
+        assert_eq!(urlencode(s), encoded);
+    }
+
+    #[rstest]
+    #[case("main", "main")]
+    #[case("feature/foo bar", "feature/foo%20bar")]
+    #[case("release/v1.0#2", "release/v1.0%232")]
+    fn test_urlencode_ref(#[case] s: &str, #[case] encoded: &str) {
+        assert_eq!(urlencode_ref(s), encoded);
+    }
+
+    #[rstest]
+    #[case(CiStatus::None, CiStatus::Success, CiStatus::Success)]
+    #[case(CiStatus::Success, CiStatus::Pending, CiStatus::Pending)]
+    #[case(CiStatus::Failure, CiStatus::Pending, CiStatus::Failure)]
+    #[case(CiStatus::None, CiStatus::None, CiStatus::None)]
+    fn test_combine_ci_status(#[case] a: CiStatus, #[case] b: CiStatus, #[case] ab: CiStatus) {
+        assert_eq!(a.combine(b), ab);
+        assert_eq!(b.combine(a), ab);
+    }
+
+    #[rstest]
+    #[case("[1, 2, 3]", Some(vec![1, 2, 3]))]
+    #[case(r#"{"total_count": 2, "check_runs": [4, 5]}"#, Some(vec![4, 5]))]
+    #[case(r#"{"items": [], "incomplete_results": false}"#, Some(Vec::new()))]
+    #[case(r#"{"a": [1], "b": [2]}"#, None)]
+    #[case(r#"{"total_count": 0}"#, None)]
+    fn test_page_items(#[case] src: &str, #[case] items: Option<Vec<u32>>) {
+        let page = serde_json::from_str::<Page<u32>>(src).unwrap();
+        assert_eq!(page.into_items().ok(), items);
+    }
+}
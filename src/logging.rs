@@ -1,5 +1,5 @@
 use crate::cmd::{CommandError, CommandPlus};
-use crate::project::Project;
+use crate::project::{Project, WorkspaceMember};
 use anstyle::{AnsiColor, Style};
 use indenter::indented;
 use log::{Log, Metadata, Record};
@@ -65,6 +65,17 @@ pub(crate) fn logproject(p: &Project) {
     );
 }
 
+/// Output the name of a member of a Rust workspace project, qualified by the
+/// project name
+pub(crate) fn logmember(p: &Project, member: &WorkspaceMember) {
+    anstream::println!(
+        "{bold}{project}/{member}{bold:#}",
+        project = p.name(),
+        member = member.name,
+        bold = Style::new().bold()
+    );
+}
+
 pub(crate) fn logcmd(cmd: &CommandPlus, level: Verbosity) {
     if is_active(level) {
         anstream::eprintln!(
@@ -103,6 +114,14 @@ pub(crate) fn logln(level: Verbosity, style: Style, fmtargs: fmt::Arguments<'_>)
     }
 }
 
+/// Log a non-fatal error as a warning
+pub(crate) fn logwarning(e: &anyhow::Error) {
+    if is_active(Verbosity::Quiet) {
+        let style = Style::new().fg_color(Some(AnsiColor::Magenta.into()));
+        anstream::eprintln!("{style}[!] Warning: {e:#}{style:#}");
+    }
+}
+
 pub(crate) fn logerror(e: anyhow::Error) {
     let style = Style::new().fg_color(Some(AnsiColor::BrightRed.into()));
     anstream::eprintln!("{style}[!] {e}{style:#}");
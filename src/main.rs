@@ -1,11 +1,19 @@
 #[macro_use]
 mod logging;
 
+mod changelog;
 mod cmd;
 mod commands;
+mod depgraph;
 mod finder;
+mod forge;
 mod github;
+mod pep440;
 mod project;
+mod registry;
+mod sweep;
+mod table;
+mod template;
 mod util;
 use crate::commands::Command;
 use crate::finder::Finder;
@@ -35,14 +43,14 @@ fn main() -> ExitCode {
         command,
     } = Arguments::parse();
     init_logging(opts.verbosity());
-    let projects = match finder.findall() {
-        Ok(projects) => projects,
+    let discovery = match finder.findall() {
+        Ok(discovery) => discovery,
         Err(e) => {
             logerror(e.context("Failed to list projects"));
             return ExitCode::FAILURE;
         }
     };
-    command.run(opts, projects)
+    command.run(opts, discovery)
 }
 
 #[cfg(test)]
@@ -63,7 +71,10 @@ mod tests {
                     shell: false,
                     command: vec![OsString::from("cmd"), OsString::from("--shell")],
                 },
-                stash: false
+                stash: false,
+                no_restore: false,
+                per_member: false,
+                log_dir: None,
             })
         );
     }
@@ -79,7 +90,10 @@ mod tests {
                     shell: false,
                     command: vec![OsString::from("cmd"), OsString::from("-x")],
                 },
-                stash: false
+                stash: false,
+                no_restore: false,
+                per_member: false,
+                log_dir: None,
             })
         );
     }
@@ -0,0 +1,398 @@
+use itertools::Itertools;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A Python package version as specified by PEP 440.  Local version labels
+/// are accepted but ignored.
+#[derive(Clone, Debug)]
+pub(crate) struct Version {
+    epoch: u64,
+    release: Vec<u64>,
+    pre: Option<(PreKind, u64)>,
+    post: Option<u64>,
+    dev: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum PreKind {
+    Alpha,
+    Beta,
+    Rc,
+}
+
+impl Version {
+    pub(crate) fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+
+    /// Test whether the release segment of `self` starts with `prefix`
+    /// (padding `self` with zeroes as needed) and the epochs are equal
+    fn has_prefix(&self, epoch: u64, prefix: &[u64]) -> bool {
+        self.epoch == epoch
+            && prefix
+                .iter()
+                .enumerate()
+                .all(|(i, &n)| self.release.get(i).copied().unwrap_or_default() == n)
+    }
+
+    /// Return a key that orders versions as described by PEP 440
+    fn cmp_key(&self) -> impl Ord + '_ {
+        let mut release = self.release.as_slice();
+        while let Some((&0, rest)) = release.split_last() {
+            release = rest;
+        }
+        // A developmental release of a final release sorts before all of the
+        // pre-releases of that release; otherwise, final releases sort after
+        // pre-releases.
+        let pre = match (self.pre, self.post, self.dev) {
+            (Some((kind, n)), _, _) => (1, Some(kind), n),
+            (None, None, Some(_)) => (0, None, 0),
+            (None, _, _) => (2, None, 0),
+        };
+        let dev = match self.dev {
+            Some(n) => (0, n),
+            None => (1, 0),
+        };
+        (self.epoch, release, pre, self.post, dev)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Version) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Version) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Version) -> Ordering {
+        self.cmp_key().cmp(&other.cmp_key())
+    }
+}
+
+impl fmt::Display for Version {
+    /// Display the version in normalized form
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.epoch != 0 {
+            write!(f, "{}!", self.epoch)?;
+        }
+        write!(f, "{}", self.release.iter().format("."))?;
+        if let Some((kind, n)) = self.pre {
+            let kind = match kind {
+                PreKind::Alpha => "a",
+                PreKind::Beta => "b",
+                PreKind::Rc => "rc",
+            };
+            write!(f, "{kind}{n}")?;
+        }
+        if let Some(n) = self.post {
+            write!(f, ".post{n}")?;
+        }
+        if let Some(n) = self.dev {
+            write!(f, ".dev{n}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Version, ParseVersionError> {
+        let lower = s.trim().to_ascii_lowercase();
+        let public = lower.split_once('+').map_or(lower.as_str(), |(v, _)| v);
+        let mut cur = Cursor(public.strip_prefix('v').unwrap_or(public));
+        let mut epoch = 0;
+        let mut release = vec![cur.number().ok_or(ParseVersionError)?];
+        if cur.eat("!") {
+            epoch = release[0];
+            release[0] = cur.number().ok_or(ParseVersionError)?;
+        }
+        while let Some(n) = cur.attempt(|c| c.eat(".").then(|| c.number()).flatten()) {
+            release.push(n);
+        }
+        let pre = cur.attempt(|c| {
+            c.separator();
+            let kind = if c.eat("alpha") || c.eat("a") {
+                PreKind::Alpha
+            } else if c.eat("beta") || c.eat("b") {
+                PreKind::Beta
+            } else if c.eat("preview") || c.eat("pre") || c.eat("rc") || c.eat("c") {
+                PreKind::Rc
+            } else {
+                return None;
+            };
+            Some((kind, c.optional_number()))
+        });
+        let post = cur
+            .attempt(|c| c.eat("-").then(|| c.number()).flatten())
+            .or_else(|| {
+                cur.attempt(|c| {
+                    c.separator();
+                    (c.eat("post") || c.eat("rev") || c.eat("r")).then(|| c.optional_number())
+                })
+            });
+        let dev = cur.attempt(|c| {
+            c.separator();
+            c.eat("dev").then(|| c.optional_number())
+        });
+        if !cur.0.is_empty() {
+            return Err(ParseVersionError);
+        }
+        Ok(Version {
+            epoch,
+            release,
+            pre,
+            post,
+            dev,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+#[error("invalid PEP 440 version")]
+pub(crate) struct ParseVersionError;
+
+/// A set of PEP 440 version specifiers, e.g., `>=1.2,<2`.  An empty set
+/// matches all versions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct SpecifierSet(Vec<Specifier>);
+
+impl SpecifierSet {
+    pub(crate) fn contains(&self, v: &Version) -> bool {
+        self.0.iter().all(|spec| spec.contains(v))
+    }
+}
+
+impl FromStr for SpecifierSet {
+    type Err = ParseSpecifierError;
+
+    fn from_str(s: &str) -> Result<SpecifierSet, ParseSpecifierError> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(SpecifierSet(Vec::new()));
+        }
+        s.split(',')
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(SpecifierSet)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Specifier {
+    op: Operator,
+    version: Version,
+    /// Whether the version ended with `.*`
+    wildcard: bool,
+}
+
+impl Specifier {
+    fn contains(&self, v: &Version) -> bool {
+        match self.op {
+            Operator::Equal if self.wildcard => {
+                v.has_prefix(self.version.epoch, &self.version.release)
+            }
+            Operator::Equal => *v == self.version,
+            Operator::NotEqual if self.wildcard => {
+                !v.has_prefix(self.version.epoch, &self.version.release)
+            }
+            Operator::NotEqual => *v != self.version,
+            Operator::Compatible => {
+                let prefix = &self.version.release[..self.version.release.len() - 1];
+                *v >= self.version && v.has_prefix(self.version.epoch, prefix)
+            }
+            Operator::LessEqual => *v <= self.version,
+            Operator::GreaterEqual => *v >= self.version,
+            Operator::Less => *v < self.version,
+            Operator::Greater => *v > self.version,
+            // `===` is meant to compare version strings literally, but
+            // comparing parsed versions is close enough for our purposes
+            Operator::Arbitrary => *v == self.version,
+        }
+    }
+}
+
+impl FromStr for Specifier {
+    type Err = ParseSpecifierError;
+
+    fn from_str(s: &str) -> Result<Specifier, ParseSpecifierError> {
+        let s = s.trim();
+        let (op, rest) = OPERATORS
+            .iter()
+            .find_map(|&(sym, op)| s.strip_prefix(sym).map(|rest| (op, rest.trim())))
+            .ok_or_else(|| ParseSpecifierError(s.to_owned()))?;
+        let (vstr, wildcard) = match rest.strip_suffix(".*") {
+            Some(v) if matches!(op, Operator::Equal | Operator::NotEqual) => (v, true),
+            Some(_) => return Err(ParseSpecifierError(s.to_owned())),
+            None => (rest, false),
+        };
+        let version = vstr
+            .parse::<Version>()
+            .map_err(|_| ParseSpecifierError(s.to_owned()))?;
+        if op == Operator::Compatible && version.release.len() < 2 {
+            return Err(ParseSpecifierError(s.to_owned()));
+        }
+        Ok(Specifier {
+            op,
+            version,
+            wildcard,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("invalid PEP 440 version specifier: {0:?}")]
+pub(crate) struct ParseSpecifierError(String);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Operator {
+    Compatible,
+    Equal,
+    NotEqual,
+    LessEqual,
+    GreaterEqual,
+    Less,
+    Greater,
+    Arbitrary,
+}
+
+/// Specifier operators, with longer operators before their prefixes
+static OPERATORS: &[(&str, Operator)] = &[
+    ("===", Operator::Arbitrary),
+    ("~=", Operator::Compatible),
+    ("==", Operator::Equal),
+    ("!=", Operator::NotEqual),
+    ("<=", Operator::LessEqual),
+    (">=", Operator::GreaterEqual),
+    ("<", Operator::Less),
+    (">", Operator::Greater),
+];
+
+/// A position in a string being parsed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Cursor<'a>(&'a str);
+
+impl Cursor<'_> {
+    fn eat(&mut self, prefix: &str) -> bool {
+        if let Some(rest) = self.0.strip_prefix(prefix) {
+            self.0 = rest;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume an optional `.`, `-`, or `_` separator
+    fn separator(&mut self) {
+        let _ = self.eat(".") || self.eat("-") || self.eat("_");
+    }
+
+    fn number(&mut self) -> Option<u64> {
+        let end = self
+            .0
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.0.len());
+        let n = self.0[..end].parse().ok()?;
+        self.0 = &self.0[end..];
+        Some(n)
+    }
+
+    /// Consume a number preceded by an optional separator, defaulting to 0
+    /// if there is no number
+    fn optional_number(&mut self) -> u64 {
+        self.attempt(|c| {
+            c.separator();
+            c.number()
+        })
+        .unwrap_or_default()
+    }
+
+    /// Run `f` on the cursor, restoring the cursor's position if `f` returns
+    /// `None`
+    fn attempt<T, F: FnOnce(&mut Self) -> Option<T>>(&mut self, f: F) -> Option<T> {
+        let saved = *self;
+        let r = f(self);
+        if r.is_none() {
+            *self = saved;
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_version_order() {
+        let versions = [
+            "1.0.dev0",
+            "1.0a1",
+            "1.0a2.dev1",
+            "1.0a2",
+            "1.0b1",
+            "1.0rc1",
+            "1.0",
+            "1.0.post1.dev1",
+            "1.0.post1",
+            "1.0.1",
+            "1.1",
+            "1!0.1",
+        ];
+        for (s1, s2) in versions.into_iter().tuple_windows() {
+            let v1 = s1.parse::<Version>().unwrap();
+            let v2 = s2.parse::<Version>().unwrap();
+            assert!(v1 < v2, "{s1} should be less than {s2}");
+        }
+    }
+
+    #[rstest]
+    #[case("01.00.0", "1.0.0")]
+    #[case("1.0-1", "1.0.post1")]
+    #[case("v1.0RC1", "1.0rc1")]
+    #[case("1.0-alpha.2", "1.0a2")]
+    #[case("1.0+local.7", "1.0")]
+    fn test_version_equivalent(#[case] v1: &str, #[case] v2: &str) {
+        let v1 = v1.parse::<Version>().unwrap();
+        assert_eq!(v1, v2.parse::<Version>().unwrap());
+        assert_eq!(v1.to_string(), v2);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("foo")]
+    #[case("1.0.")]
+    #[case("1.0 beta")]
+    fn test_bad_version(#[case] s: &str) {
+        assert_eq!(s.parse::<Version>(), Err(ParseVersionError));
+    }
+
+    #[rstest]
+    #[case("", "1.0", true)]
+    #[case(">=1.2, <2", "1.5", true)]
+    #[case(">=1.2, <2", "2.0", false)]
+    #[case("~=1.4.2", "1.4.9", true)]
+    #[case("~=1.4.2", "1.5.0", false)]
+    #[case("~=1.4", "1.9", true)]
+    #[case("==1.4.*", "1.4.7", true)]
+    #[case("==1.4.*", "1.40", false)]
+    #[case("!=1.4.*", "1.5", true)]
+    #[case("==2", "2.0.0", true)]
+    #[case(">1.0", "1.0", false)]
+    fn test_specifier_set(#[case] specs: &str, #[case] v: &str, #[case] contained: bool) {
+        let specs = specs.parse::<SpecifierSet>().unwrap();
+        let v = v.parse::<Version>().unwrap();
+        assert_eq!(specs.contains(&v), contained);
+    }
+}
@@ -0,0 +1,608 @@
+use super::normalize_name;
+use super::python::split_requirement;
+use crate::pep440;
+use anyhow::Context;
+use cargo_metadata::semver;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, Item, TableLike, Value};
+
+/// The tables in a `Cargo.toml` file (either at the top level or under a
+/// `target.*` table) that list dependencies
+static CARGO_DEP_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Operators of lower-bound clauses that can be carried over from an old
+/// Python (or Poetry) version requirement to a new one.  Longer operators
+/// come before their prefixes.
+static PYTHON_OPERATORS: &[&str] = &["===", "==", "~=", ">=", "^", "~"];
+
+/// Operators of lower-bound clauses that can be carried over from an old
+/// Cargo version requirement to a new one
+static CARGO_OPERATORS: &[&str] = &["=", ">=", "^", "~"];
+
+/// Characters that a new requirement can start with in order to be used
+/// as-is instead of being combined with the old requirement
+static OPERATOR_CHARS: &[char] = &['<', '>', '=', '!', '~', '^', '*'];
+
+/// A set of edits to a project's manifests that update the requirements on a
+/// dependency
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DependencyBump {
+    /// The paths to the modified manifests, paired with their new contents
+    manifests: Vec<(PathBuf, DocumentMut)>,
+
+    /// Descriptions of the updated requirements
+    changes: Vec<String>,
+}
+
+impl DependencyBump {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub(crate) fn changes(&self) -> &[String] {
+        &self.changes
+    }
+
+    /// Write the modified manifests to disk
+    pub(crate) fn save(&self) -> anyhow::Result<()> {
+        for (path, doc) in &self.manifests {
+            fs_err::write(path, doc.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Parse the manifest at `dirpath.join(relpath)`, edit it with `f`, and,
+    /// if `f` reports any changes, record the edited manifest
+    pub(super) fn edit<F>(&mut self, dirpath: &Path, relpath: &Path, f: F) -> anyhow::Result<()>
+    where
+        F: FnOnce(&mut DocumentMut) -> Edits,
+    {
+        let path = dirpath.join(relpath);
+        let mut doc = fs_err::read_to_string(&path)?
+            .parse::<DocumentMut>()
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        let Edits {
+            changes,
+            unbumpable,
+        } = f(&mut doc);
+        if !unbumpable.is_empty() {
+            anyhow::bail!(
+                "{}: cannot mechanically update requirements {}; specify a full \
+                 requirement (starting with an operator) to replace them",
+                relpath.display(),
+                unbumpable.join(", ")
+            );
+        }
+        if !changes.is_empty() {
+            self.changes.extend(
+                changes
+                    .into_iter()
+                    .map(|c| format!("{}: {c}", relpath.display())),
+            );
+            self.manifests.push((path, doc));
+        }
+        Ok(())
+    }
+}
+
+/// The results of updating the requirements on a dependency in a manifest
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct Edits {
+    /// Descriptions of the updated requirements
+    changes: Vec<String>,
+
+    /// Descriptions of the requirements that could not be updated without
+    /// losing or contradicting some of their constraints
+    unbumpable: Vec<String>,
+}
+
+/// Update the version requirement on the package `name` in a `Cargo.toml`
+/// file for the version `req`, returning the edits made
+///
+/// If `req` starts with a comparison operator, it is used as the new
+/// requirement as-is; otherwise, it replaces the version in the lower-bound
+/// clause of the old requirement, keeping its operator and any other clauses.
+pub(super) fn bump_cargo_toml(doc: &mut DocumentMut, name: &str, req: &str) -> Edits {
+    let mut edits = Edits::default();
+    let new_req = |old: &str| cargo_requirement(old, req);
+    for &kind in CARGO_DEP_TABLES {
+        if let Some(table) = doc.get_mut(kind).and_then(Item::as_table_like_mut) {
+            bump_dep_table(table, kind, name, &new_req, &mut edits);
+        }
+    }
+    if let Some(targets) = doc.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (target, item) in targets.iter_mut() {
+            for &kind in CARGO_DEP_TABLES {
+                if let Some(table) = item.get_mut(kind).and_then(Item::as_table_like_mut) {
+                    let path = format!("target.{}.{kind}", target.get());
+                    bump_dep_table(table, &path, name, &new_req, &mut edits);
+                }
+            }
+        }
+    }
+    if let Some(table) = doc
+        .get_mut("workspace")
+        .and_then(|ws| ws.get_mut("dependencies"))
+        .and_then(Item::as_table_like_mut)
+    {
+        bump_dep_table(table, "workspace.dependencies", name, &new_req, &mut edits);
+    }
+    edits
+}
+
+/// Update the version requirement on the package `name` in a
+/// `pyproject.toml` file for the version `version`, returning the edits made.
+/// Requirements are updated in the `[project]` dependency lists, in
+/// `[dependency-groups]`, and in Poetry's dependency tables.
+///
+/// If `version` starts with a comparison operator, it is used as the new
+/// version specifier as-is; otherwise, it replaces the version in the
+/// lower-bound clause of the old specifier, keeping its operator (or adding a
+/// `>=` clause if there is none) and any other clauses.
+pub(super) fn bump_pyproject(doc: &mut DocumentMut, name: &str, version: &str) -> Edits {
+    let mut edits = Edits::default();
+    if let Some(project) = doc.get_mut("project") {
+        if let Some(reqs) = project.get_mut("dependencies").and_then(Item::as_array_mut) {
+            bump_req_array(reqs, "project.dependencies", name, version, &mut edits);
+        }
+        if let Some(extras) = project
+            .get_mut("optional-dependencies")
+            .and_then(Item::as_table_like_mut)
+        {
+            for (extra, item) in extras.iter_mut() {
+                if let Some(reqs) = item.as_array_mut() {
+                    let path = format!("project.optional-dependencies.{}", extra.get());
+                    bump_req_array(reqs, &path, name, version, &mut edits);
+                }
+            }
+        }
+    }
+    if let Some(groups) = doc
+        .get_mut("dependency-groups")
+        .and_then(Item::as_table_like_mut)
+    {
+        for (group, item) in groups.iter_mut() {
+            if let Some(reqs) = item.as_array_mut() {
+                let path = format!("dependency-groups.{}", group.get());
+                bump_req_array(reqs, &path, name, version, &mut edits);
+            }
+        }
+    }
+    if let Some(poetry) = doc.get_mut("tool").and_then(|t| t.get_mut("poetry")) {
+        let new_req = |old: &str| python_specifier(old, version);
+        for kind in ["dependencies", "dev-dependencies"] {
+            if let Some(table) = poetry.get_mut(kind).and_then(Item::as_table_like_mut) {
+                let path = format!("tool.poetry.{kind}");
+                bump_dep_table(table, &path, name, &new_req, &mut edits);
+            }
+        }
+        if let Some(groups) = poetry.get_mut("group").and_then(Item::as_table_like_mut) {
+            for (group, item) in groups.iter_mut() {
+                if let Some(table) = item
+                    .get_mut("dependencies")
+                    .and_then(Item::as_table_like_mut)
+                {
+                    let path = format!("tool.poetry.group.{}.dependencies", group.get());
+                    bump_dep_table(table, &path, name, &new_req, &mut edits);
+                }
+            }
+        }
+    }
+    edits
+}
+
+/// Update the requirement on the package `name` in a table mapping package
+/// names to either requirement strings or tables with `version` keys (as used
+/// by Cargo and Poetry).  `new_req` is called with each old requirement string
+/// to produce the new one, returning `None` if it cannot be updated.  `path`
+/// is the dotted path to the table, for use in change descriptions.
+fn bump_dep_table(
+    table: &mut dyn TableLike,
+    path: &str,
+    name: &str,
+    new_req: &dyn Fn(&str) -> Option<String>,
+    edits: &mut Edits,
+) {
+    let name = normalize_name(name);
+    for (key, item) in table.iter_mut() {
+        // Cargo dependencies can be renamed with the `package` key
+        let package = item
+            .get("package")
+            .and_then(Item::as_str)
+            .unwrap_or_else(|| key.get());
+        if normalize_name(package) != name {
+            continue;
+        }
+        let target = if item.is_str() {
+            Some(item)
+        } else {
+            item.get_mut("version")
+        };
+        let Some(value) = target.and_then(Item::as_value_mut) else {
+            // Path, Git, and workspace-inherited dependencies have no version
+            continue;
+        };
+        let Some(old) = value.as_str().map(ToOwned::to_owned) else {
+            continue;
+        };
+        let Some(new) = new_req(&old) else {
+            edits
+                .unbumpable
+                .push(format!("{path}.{}: {old:?}", key.get()));
+            continue;
+        };
+        if new != old {
+            replace_value(value, &new);
+            edits
+                .changes
+                .push(format!("{path}.{}: {old:?} -> {new:?}", key.get()));
+        }
+    }
+}
+
+/// Update the requirement on the package `name` in an array of PEP 508
+/// requirement strings
+fn bump_req_array(reqs: &mut Array, path: &str, name: &str, version: &str, edits: &mut Edits) {
+    for value in reqs.iter_mut() {
+        let Some(old) = value.as_str().map(ToOwned::to_owned) else {
+            continue;
+        };
+        match bump_requirement(&old, name, version) {
+            Bumped::Unchanged => (),
+            Bumped::Changed(new) => {
+                replace_value(value, &new);
+                edits.changes.push(format!("{path}: {old:?} -> {new:?}"));
+            }
+            Bumped::Unbumpable => edits.unbumpable.push(format!("{path}: {old:?}")),
+        }
+    }
+}
+
+/// The result of updating a single PEP 508 requirement string
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Bumped {
+    /// The requirement is for a different package, is a URL requirement, or
+    /// is already up to date
+    Unchanged,
+    /// The updated requirement
+    Changed(String),
+    /// The requirement cannot be updated without losing or contradicting
+    /// some of its constraints
+    Unbumpable,
+}
+
+/// If the PEP 508 requirement string `req` is a requirement on the package
+/// `name`, update its version specifier for `version`
+fn bump_requirement(req: &str, name: &str, version: &str) -> Bumped {
+    let (pkg, rest) = split_requirement(req);
+    if pkg.trim_start().is_empty() || normalize_name(pkg.trim_start()) != normalize_name(name) {
+        return Bumped::Unchanged;
+    }
+    let (rest, marker) = rest.split_at(rest.find(';').unwrap_or(rest.len()));
+    let (extras, spec) = if rest.trim_start().starts_with('[') {
+        match rest.find(']') {
+            Some(i) => rest.split_at(i + 1),
+            None => return Bumped::Unchanged,
+        }
+    } else {
+        ("", rest)
+    };
+    let trimmed = spec.trim();
+    if trimmed.starts_with('@') {
+        return Bumped::Unchanged;
+    }
+    let (lead, trail) = if trimmed.is_empty() {
+        ("", spec)
+    } else {
+        (
+            &spec[..spec.len() - spec.trim_start().len()],
+            &spec[spec.trim_end().len()..],
+        )
+    };
+    let old = trimmed
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .unwrap_or(trimmed)
+        .trim();
+    let Some(specifier) = python_specifier(old, version) else {
+        return Bumped::Unbumpable;
+    };
+    let new = format!("{pkg}{extras}{lead}{specifier}{trail}{marker}");
+    if new == req {
+        Bumped::Unchanged
+    } else {
+        Bumped::Changed(new)
+    }
+}
+
+/// Return the version specifier to use in place of the Python (or Poetry)
+/// version specifier `old` in order to require `version`, or `None` if `old`
+/// cannot be updated mechanically
+fn python_specifier(old: &str, version: &str) -> Option<String> {
+    if version.starts_with(OPERATOR_CHARS) {
+        return Some(version.to_owned());
+    }
+    let new = bump_clauses(old, version, PYTHON_OPERATORS, ">=")?;
+    if new.contains(',') {
+        // Make sure that the retained clauses admit the new version
+        let specifiers = new.parse::<pep440::SpecifierSet>().ok()?;
+        let v = version.parse::<pep440::Version>().ok()?;
+        if !specifiers.contains(&v) {
+            return None;
+        }
+    }
+    Some(new)
+}
+
+/// Return the requirement to use in place of the Cargo version requirement
+/// `old` in order to require `req`, or `None` if `old` cannot be updated
+/// mechanically
+fn cargo_requirement(old: &str, req: &str) -> Option<String> {
+    if req.starts_with(OPERATOR_CHARS) {
+        return Some(req.to_owned());
+    }
+    let new = bump_clauses(old, req, CARGO_OPERATORS, "")?;
+    if new.contains(',') {
+        // Make sure that the retained clauses admit the new version
+        let vreq = semver::VersionReq::parse(&new).ok()?;
+        let v = semver::Version::parse(req).ok()?;
+        if !vreq.matches(&v) {
+            return None;
+        }
+    }
+    Some(new)
+}
+
+/// Replace the version in the lower-bound clause of the comma-separated
+/// version requirement `old` with `version`, keeping the clause's operator
+/// (from `operators`, or none for a bare version) and the other clauses.  A
+/// `>` operator is changed to `>=` so that `version` itself is allowed.  If
+/// there is no lower-bound clause, one using `default_op` is added.
+///
+/// Returns `None` if the requirement has multiple lower bounds, uses
+/// wildcards in its lower bound, or contains `||` alternatives.
+fn bump_clauses(old: &str, version: &str, operators: &[&str], default_op: &str) -> Option<String> {
+    if old.contains("||") {
+        return None;
+    }
+    let sep = if old.contains(", ") { ", " } else { "," };
+    let mut clauses = old
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty() && *c != "*")
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    let mut lower = None;
+    for (i, clause) in clauses.iter().enumerate() {
+        let op = if clause.starts_with(|c: char| c.is_ascii_digit()) {
+            // A bare version is an exact requirement in Poetry and a caret
+            // requirement in Cargo
+            Some("")
+        } else if clause.starts_with('>') && !clause.starts_with(">=") {
+            Some(">=")
+        } else {
+            operators.iter().copied().find(|op| clause.starts_with(op))
+        };
+        if let Some(op) = op {
+            if lower.is_some() || clause.contains('*') {
+                return None;
+            }
+            lower = Some((i, op));
+        }
+    }
+    match lower {
+        Some((i, op)) => clauses[i] = format!("{op}{version}"),
+        None => clauses.insert(0, format!("{default_op}{version}")),
+    }
+    Some(clauses.join(sep))
+}
+
+/// Replace the string value `value` with `new`, keeping its surrounding
+/// whitespace and comments
+fn replace_value(value: &mut Value, new: &str) {
+    let decor = value.decor().clone();
+    *value = Value::from(new);
+    *value.decor_mut() = decor;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_bump_cargo_toml() {
+        let mut doc = concat!(
+            "[package]\n",
+            "name = \"foo\"\n",
+            "version = \"0.1.0\"\n",
+            "\n",
+            "[dependencies]\n",
+            "anyhow = \"1.0.94\"  # Error handling\n",
+            "serde = { version = \"1.0.200\", features = [\"derive\"] }\n",
+            "serde_json = \"1.0.120\"\n",
+            "ser = { package = \"serde\", version = \"1.0.100\" }\n",
+            "\n",
+            "[dev-dependencies.serde]\n",
+            "version = \"1.0.200\"\n",
+            "\n",
+            "[target.'cfg(unix)'.dependencies]\n",
+            "serde = { path = \"../serde\" }\n",
+            "\n",
+            "[workspace.dependencies]\n",
+            "serde = \"=1.0.200\"\n",
+        )
+        .parse::<DocumentMut>()
+        .unwrap();
+        let edits = bump_cargo_toml(&mut doc, "serde", "1.0.228");
+        assert_eq!(
+            edits.changes,
+            [
+                "dependencies.serde: \"1.0.200\" -> \"1.0.228\"",
+                "dependencies.ser: \"1.0.100\" -> \"1.0.228\"",
+                "dev-dependencies.serde: \"1.0.200\" -> \"1.0.228\"",
+                "workspace.dependencies.serde: \"=1.0.200\" -> \"=1.0.228\"",
+            ]
+        );
+        assert!(edits.unbumpable.is_empty());
+        assert_eq!(
+            doc.to_string(),
+            concat!(
+                "[package]\n",
+                "name = \"foo\"\n",
+                "version = \"0.1.0\"\n",
+                "\n",
+                "[dependencies]\n",
+                "anyhow = \"1.0.94\"  # Error handling\n",
+                "serde = { version = \"1.0.228\", features = [\"derive\"] }\n",
+                "serde_json = \"1.0.120\"\n",
+                "ser = { package = \"serde\", version = \"1.0.228\" }\n",
+                "\n",
+                "[dev-dependencies.serde]\n",
+                "version = \"1.0.228\"\n",
+                "\n",
+                "[target.'cfg(unix)'.dependencies]\n",
+                "serde = { path = \"../serde\" }\n",
+                "\n",
+                "[workspace.dependencies]\n",
+                "serde = \"=1.0.228\"\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_bump_cargo_toml_unbumpable() {
+        let mut doc = concat!(
+            "[dependencies]\n",
+            "serde = \">=1.0, <1.0.200\"\n",
+            "\n",
+            "[dev-dependencies]\n",
+            "serde = \"1.0.100\"\n",
+        )
+        .parse::<DocumentMut>()
+        .unwrap();
+        let edits = bump_cargo_toml(&mut doc, "serde", "1.0.228");
+        assert_eq!(
+            edits,
+            Edits {
+                changes: vec![String::from(
+                    "dev-dependencies.serde: \"1.0.100\" -> \"1.0.228\""
+                )],
+                unbumpable: vec![String::from("dependencies.serde: \">=1.0, <1.0.200\"")],
+            }
+        );
+    }
+
+    #[test]
+    fn test_bump_pyproject() {
+        let mut doc = concat!(
+            "[project]\n",
+            "name = \"foo\"\n",
+            "dependencies = [\n",
+            "    \"attrs >= 23.1\",\n",
+            "    \"Requests[socks]~=2.31 ; python_version >= '3.8'\",  # HTTP\n",
+            "]\n",
+            "\n",
+            "[project.optional-dependencies]\n",
+            "all = [\"requests\"]\n",
+            "\n",
+            "[dependency-groups]\n",
+            "test = [\"pytest\", \"requests @ https://example.com/requests.zip\"]\n",
+            "\n",
+            "[tool.poetry.group.dev.dependencies]\n",
+            "requests = \"^2.28\"\n",
+        )
+        .parse::<DocumentMut>()
+        .unwrap();
+        let edits = bump_pyproject(&mut doc, "requests", "2.32.3");
+        assert_eq!(
+            edits.changes,
+            [
+                concat!(
+                    "project.dependencies: ",
+                    "\"Requests[socks]~=2.31 ; python_version >= '3.8'\" -> ",
+                    "\"Requests[socks]~=2.32.3 ; python_version >= '3.8'\"",
+                ),
+                "project.optional-dependencies.all: \"requests\" -> \"requests>=2.32.3\"",
+                "tool.poetry.group.dev.dependencies.requests: \"^2.28\" -> \"^2.32.3\"",
+            ]
+        );
+        assert!(edits.unbumpable.is_empty());
+        assert_eq!(
+            doc.to_string(),
+            concat!(
+                "[project]\n",
+                "name = \"foo\"\n",
+                "dependencies = [\n",
+                "    \"attrs >= 23.1\",\n",
+                "    \"Requests[socks]~=2.32.3 ; python_version >= '3.8'\",  # HTTP\n",
+                "]\n",
+                "\n",
+                "[project.optional-dependencies]\n",
+                "all = [\"requests>=2.32.3\"]\n",
+                "\n",
+                "[dependency-groups]\n",
+                "test = [\"pytest\", \"requests @ https://example.com/requests.zip\"]\n",
+                "\n",
+                "[tool.poetry.group.dev.dependencies]\n",
+                "requests = \"^2.32.3\"\n",
+            )
+        );
+    }
+
+    #[rstest]
+    #[case("foo", "foo", "2.0", Bumped::Changed("foo>=2.0".into()))]
+    #[case("foo>=1.0", "foo", "2.0", Bumped::Changed("foo>=2.0".into()))]
+    #[case("foo >= 1.0, < 3", "foo", "2.0", Bumped::Changed("foo >=2.0, < 3".into()))]
+    #[case("foo >= 1.0, < 2", "foo", "2.0", Bumped::Unbumpable)]
+    #[case("foo>1.0,!=1.5", "foo", "2.0", Bumped::Changed("foo>=2.0,!=1.5".into()))]
+    #[case("foo>=1.0,!=2.0", "foo", "2.0", Bumped::Unbumpable)]
+    #[case("foo (==1.0)", "foo", "2.0", Bumped::Changed("foo ==2.0".into()))]
+    #[case("foo<3", "foo", "2.0", Bumped::Changed("foo>=2.0,<3".into()))]
+    #[case("foo<2", "foo", "2.0", Bumped::Unbumpable)]
+    #[case("foo>=1.0,==1.*", "foo", "2.0", Bumped::Unbumpable)]
+    #[case("foo>=1.0", "foo", ">=2.0,<3", Bumped::Changed("foo>=2.0,<3".into()))]
+    #[case("foo<2", "foo", ">=2.0", Bumped::Changed("foo>=2.0".into()))]
+    #[case(
+        "foo;python_version<'3.10'",
+        "foo",
+        "2.0",
+        Bumped::Changed("foo>=2.0;python_version<'3.10'".into())
+    )]
+    #[case("Foo.Bar[baz]>=1", "foo-bar", "2.0", Bumped::Changed("Foo.Bar[baz]>=2.0".into()))]
+    #[case("foo>=2.0", "foo", "2.0", Bumped::Unchanged)]
+    #[case("foobar>=1.0", "foo", "2.0", Bumped::Unchanged)]
+    #[case("foo @ file:///tmp/foo", "foo", "2.0", Bumped::Unchanged)]
+    fn test_bump_requirement(
+        #[case] req: &str,
+        #[case] name: &str,
+        #[case] version: &str,
+        #[case] bumped: Bumped,
+    ) {
+        assert_eq!(bump_requirement(req, name, version), bumped);
+    }
+
+    #[rstest]
+    #[case("^2.28", "2.32.3", Some("^2.32.3"))]
+    #[case("2.28", "2.32.3", Some("2.32.3"))]
+    #[case("*", "2.32.3", Some(">=2.32.3"))]
+    #[case(">=2.28,<3", "2.32.3", Some(">=2.32.3,<3"))]
+    #[case(">=2.28,<2.30", "2.32.3", None)]
+    #[case("^1.0 || ^2.0", "2.32.3", None)]
+    fn test_python_specifier(#[case] old: &str, #[case] version: &str, #[case] new: Option<&str>) {
+        assert_eq!(python_specifier(old, version).as_deref(), new);
+    }
+
+    #[rstest]
+    #[case("1.0.200", "1.0.228", Some("1.0.228"))]
+    #[case("=1.0.200", "1.0.228", Some("=1.0.228"))]
+    #[case("~1.0.200", "1.0.228", Some("~1.0.228"))]
+    #[case(">=1.0.200, <2", "1.0.228", Some(">=1.0.228, <2"))]
+    #[case(">=1.0.200, <1.0.220", "1.0.228", None)]
+    #[case("*", "1.0.228", Some("1.0.228"))]
+    #[case("<2", "1.0.228", Some("1.0.228,<2"))]
+    #[case("=1.0.200", "^1.0.228", Some("^1.0.228"))]
+    fn test_cargo_requirement(#[case] old: &str, #[case] req: &str, #[case] new: Option<&str>) {
+        assert_eq!(cargo_requirement(old, req).as_deref(), new);
+    }
+}
@@ -1,7 +1,8 @@
 use serde::Serialize;
+use std::fmt;
 use thiserror::Error;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub(crate) enum Language {
     Python,
     Rust,
@@ -16,6 +17,15 @@ impl Language {
     }
 }
 
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Language::Python => write!(f, "Python"),
+            Language::Rust => write!(f, "Rust"),
+        }
+    }
+}
+
 impl std::str::FromStr for Language {
     type Err = ParseLanguageError;
 
@@ -1,9 +1,14 @@
+mod bump;
 mod lang;
+mod python;
+pub(crate) use self::bump::DependencyBump;
 pub(crate) use self::lang::*;
+use self::python::PythonConfig;
 use crate::cmd::{CommandError, CommandKind, CommandPlus};
-use crate::util::get_ghrepo;
+use crate::forge::RemoteRepo;
+use crate::util::get_origin_url;
 use anyhow::Context;
-use cargo_metadata::{MetadataCommand, TargetKind};
+use cargo_metadata::{MetadataCommand, Source, TargetKind};
 use fs_err::PathExt;
 use ghrepo::GHRepo;
 use serde::{Deserialize, Serialize};
@@ -22,42 +27,68 @@ pub(crate) struct Project {
     is_workspace: bool,
     is_virtual_workspace: bool,
     ghrepo: Option<GHRepo>,
+    remote: Option<RemoteRepo>,
+    version: Option<String>,
 }
 
 impl Project {
     pub(crate) fn try_for_dirpath(p: PathBuf) -> anyhow::Result<Option<Project>> {
-        let ghrepo = get_ghrepo(&p)?;
-        let pyproject = p.join("pyproject.toml");
+        let url = get_origin_url(&p)?;
+        let ghrepo = url.as_deref().and_then(|u| GHRepo::from_url(u).ok());
+        let remote = url.as_deref().and_then(RemoteRepo::from_url);
+        let python = PythonConfig::load(&p)?;
         let cargo = p.join("Cargo.toml");
-        if pyproject.fs_err_try_exists()? {
-            let src = fs_err::read_to_string(&pyproject)?;
-            let data = toml::from_str::<Pyproject>(&src)
-                .context("failed to deserialize pyproject.toml")?;
+        // A `pyproject.toml` or `setup.cfg` that only configures tools does not
+        // make a Rust project a Python project
+        let is_python = python.as_ref().is_some_and(PythonConfig::declares_package);
+        if is_python || (python.is_some() && !cargo.fs_err_try_exists()?) {
+            let python = python.unwrap_or_default();
+            let name = match python.name() {
+                Some(name) => name,
+                None => dirname(&p)?,
+            };
             Ok(Some(Project {
                 dirpath: p,
-                name: data.project.name,
+                name,
                 language: Language::Python,
                 is_workspace: false,
                 is_virtual_workspace: false,
                 ghrepo,
+                remote,
+                version: python.version(),
             }))
         } else if cargo.fs_err_try_exists()? {
             let src = fs_err::read_to_string(&cargo)?;
             let data = toml::from_str::<Cargo>(&src)
                 .with_context(|| format!("failed to deserialize {}", cargo.display()))?;
+            let name = match data.name() {
+                Some(name) => name.to_owned(),
+                None => dirname(&p)?,
+            };
             Ok(Some(Project {
                 dirpath: p,
-                name: data.name().to_owned(),
+                name,
                 language: Language::Rust,
                 is_workspace: data.is_workspace(),
                 is_virtual_workspace: data.is_virtual_workspace(),
+                version: data.version().map(ToOwned::to_owned),
                 ghrepo,
+                remote,
             }))
         } else {
             Ok(None)
         }
     }
 
+    /// Return a copy of the project located at a different directory, such
+    /// as a worktree of the project's repository
+    pub(crate) fn with_dirpath(&self, dirpath: PathBuf) -> Project {
+        Project {
+            dirpath,
+            ..self.clone()
+        }
+    }
+
     pub(crate) fn name(&self) -> &str {
         &self.name
     }
@@ -78,27 +109,158 @@ impl Project {
         self.ghrepo.as_ref()
     }
 
+    /// Return the repository on a code hosting service (GitHub or otherwise)
+    /// that the project's `origin` remote points to
+    pub(crate) fn remote(&self) -> Option<&RemoteRepo> {
+        self.remote.as_ref()
+    }
+
+    /// Return the version declared in the project's manifest, if any.  For
+    /// virtual Rust workspaces, this is `workspace.package.version`.
+    pub(crate) fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
     pub(crate) fn on_default_branch(&self) -> anyhow::Result<bool> {
         let current = self.readcmd("git", ["symbolic-ref", "--short", "-q", "HEAD"])?;
-        Ok(DEFAULT_BRANCHES.iter().any(|&b| b == current))
+        match self.remote_default_branch()? {
+            Some(defbranch) => Ok(defbranch == current),
+            None => Ok(DEFAULT_BRANCHES.iter().any(|&b| b == current)),
+        }
     }
 
-    pub(crate) fn default_branch(&self) -> anyhow::Result<&'static str> {
-        let branches = self
-            .readcmd("git", ["branch", "--format=%(refname:short)"])?
-            .lines()
-            .map(ToString::to_string)
-            .collect::<HashSet<_>>();
+    /// Return the name of the project's default branch.  This is the branch
+    /// that `origin/HEAD` points to, if it is set (as it is in clones);
+    /// otherwise, it is the first of `main` and `master` that exists locally.
+    pub(crate) fn default_branch(&self) -> anyhow::Result<String> {
+        if let Some(defbranch) = self.remote_default_branch()? {
+            return Ok(defbranch);
+        }
+        let branches = self.local_branches()?.into_iter().collect::<HashSet<_>>();
         for &guess in DEFAULT_BRANCHES {
             if branches.contains(guess) {
-                return Ok(guess);
+                return Ok(guess.to_owned());
             }
         }
         anyhow::bail!("Could not determine default branch for {}", self.name())
     }
 
-    pub(crate) fn to_details(&self) -> anyhow::Result<ProjectDetails> {
+    /// Return the name of the branch that `origin/HEAD` points to, if set
+    fn remote_default_branch(&self) -> anyhow::Result<Option<String>> {
+        let r = self.readcmd(
+            "git",
+            ["symbolic-ref", "--short", "-q", "refs/remotes/origin/HEAD"],
+        );
+        match r {
+            Ok(gitref) => Ok(gitref.strip_prefix("origin/").map(ToOwned::to_owned)),
+            Err(CommandError::Exit { rc, .. }) if rc.code() == Some(1) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Return the name of the currently checked-out branch, or `None` if
+    /// `HEAD` is detached
+    pub(crate) fn current_branch(&self) -> anyhow::Result<Option<String>> {
+        match self.readcmd("git", ["symbolic-ref", "--short", "-q", "HEAD"]) {
+            Ok(branch) => Ok(Some(branch)),
+            Err(CommandError::Exit { rc, .. }) if rc.code() == Some(1) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Return the most recent tag reachable from `HEAD`, or `None` if there
+    /// is no such tag
+    pub(crate) fn latest_tag(&self) -> anyhow::Result<Option<String>> {
+        match self.readcmd("git", ["describe", "--tags", "--abbrev=0"]) {
+            Ok(tag) => Ok(Some(tag)),
+            Err(CommandError::Exit { rc, .. }) if rc.code() == Some(128) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Return the number of commits reachable from `HEAD` but not from
+    /// `since`, or the total number of commits on `HEAD` if `since` is `None`
+    pub(crate) fn count_commits(&self, since: Option<&str>) -> anyhow::Result<u64> {
+        let range = match since {
+            Some(rev) => format!("{rev}..HEAD"),
+            None => String::from("HEAD"),
+        };
+        let count = self.readcmd("git", ["rev-list", "--count", &range])?;
+        count
+            .parse::<u64>()
+            .with_context(|| format!("failed to parse `git rev-list --count` output {count:?}"))
+    }
+
+    pub(crate) fn local_branches(&self) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .readcmd("git", ["branch", "--format=%(refname:short)"])?
+            .lines()
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    /// Return the commit hash that the local branch `branch` points to, or
+    /// `None` if there is no such branch
+    pub(crate) fn branch_commit(&self, branch: &str) -> anyhow::Result<Option<String>> {
+        let gitref = format!("refs/heads/{branch}");
+        match self.readcmd("git", ["rev-parse", "--verify", "--quiet", &gitref]) {
+            Ok(oid) => Ok(Some(oid)),
+            Err(CommandError::Exit { rc, .. }) if rc.code() == Some(1) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Delete the local branch `branch`, if it exists.  If it is currently
+    /// checked out, the default branch is checked out first.
+    ///
+    /// Unless `force` is true, the branch is only deleted if it has been
+    /// merged into the default branch; otherwise (as happens with branches
+    /// that were squash- or rebase-merged upstream), a message is emitted and
+    /// the branch is left in place.
+    pub(crate) fn delete_local_branch(&self, branch: &str, force: bool) -> anyhow::Result<()> {
+        if !self.local_branches()?.iter().any(|b| b == branch) {
+            return Ok(());
+        }
+        let defbranch = self.default_branch()?;
+        if !force
+            && !self
+                .merged_branches(&defbranch)?
+                .iter()
+                .any(|b| b == branch)
+        {
+            info!("Local branch {branch:?} has not been merged into {defbranch:?}; not deleting");
+            return Ok(());
+        }
+        if self.current_branch()?.as_deref() == Some(branch) {
+            self.runcmd("git").arg("checkout").arg(&defbranch).run()?;
+        }
+        self.runcmd("git")
+            .arg("branch")
+            .arg("-D")
+            .arg(branch)
+            .run()?;
+        Ok(())
+    }
+
+    /// Return the names of the local branches that have been merged into
+    /// `target`
+    fn merged_branches(&self, target: &str) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .readcmd(
+                "git",
+                ["branch", "--merged", target, "--format=%(refname:short)"],
+            )?
+            .lines()
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    /// Return details about the project.  The members of Rust workspaces are
+    /// only included if `with_members` is true, as determining them requires
+    /// running `cargo metadata`.
+    pub(crate) fn to_details(&self, with_members: bool) -> anyhow::Result<ProjectDetails> {
         Ok(ProjectDetails {
+            members: with_members.then(|| self.members()).transpose()?,
             name: self.name.clone(),
             dirpath: self.dirpath.clone(),
             on_default_branch: self.on_default_branch()?,
@@ -117,10 +279,169 @@ impl Project {
         self.is_virtual_workspace
     }
 
+    /// Return the member packages of a Rust workspace, including the root
+    /// package of a non-virtual workspace.  Returns an empty list for
+    /// projects that are not workspaces.
+    pub(crate) fn members(&self) -> anyhow::Result<Vec<WorkspaceMember>> {
+        if !self.is_workspace {
+            return Ok(Vec::new());
+        }
+        let packages = self.cargo_packages()?;
+        let root = fs_err::canonicalize(&self.dirpath)?;
+        let mut members = Vec::with_capacity(packages.len());
+        for pkg in packages {
+            let Some(pkgdir) = pkg.manifest_path.parent() else {
+                anyhow::bail!(
+                    "Could not determine parent directory of manifest_path {} for project {}",
+                    pkg.manifest_path,
+                    self.name()
+                );
+            };
+            let dirpath = pkgdir
+                .as_std_path()
+                .strip_prefix(&root)
+                .map_or_else(|_| pkgdir.as_std_path().to_owned(), Path::to_path_buf);
+            let dirpath = if dirpath.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                dirpath
+            };
+            members.push(WorkspaceMember {
+                name: pkg.name.to_string(),
+                version: pkg.version.to_string(),
+                dirpath,
+            });
+        }
+        members.sort_by(|a, b| a.dirpath.cmp(&b.dirpath));
+        Ok(members)
+    }
+
+    /// Return the packages in a Rust project (the root package and/or any
+    /// workspace members) as reported by `cargo metadata`
+    fn cargo_packages(&self) -> anyhow::Result<Vec<cargo_metadata::Package>> {
+        Ok(MetadataCommand::new()
+            .manifest_path(self.dirpath.join("Cargo.toml"))
+            .no_deps()
+            .exec()
+            .context("failed to get project metadata")?
+            .packages)
+    }
+
+    /// Return the normalized names of the packages that the project provides
+    /// and depends on.  For Rust projects, this covers all workspace members
+    /// and all kinds of dependencies.
+    pub(crate) fn package_deps(&self) -> anyhow::Result<PackageDeps> {
+        match self.language {
+            Language::Python => {
+                let requires = PythonConfig::load(&self.dirpath)?
+                    .map(|cfg| cfg.dependencies())
+                    .unwrap_or_default();
+                Ok(PackageDeps {
+                    provides: vec![normalize_name(&self.name)],
+                    requires,
+                })
+            }
+            Language::Rust => {
+                let packages = self.cargo_packages()?;
+                let mut provides = Vec::with_capacity(packages.len());
+                let mut requires = Vec::new();
+                for pkg in packages {
+                    provides.push(normalize_name(&pkg.name));
+                    requires.extend(pkg.dependencies.iter().map(|d| normalize_name(&d.name)));
+                }
+                requires.sort();
+                requires.dedup();
+                Ok(PackageDeps { provides, requires })
+            }
+        }
+    }
+
+    /// Return the project's requirements on packages from crates.io (for Rust
+    /// projects) or the Python Package Index (for Python projects).  For
+    /// Python projects, dependencies declared in Poetry's tables are not
+    /// included.
+    pub(crate) fn requirements(&self) -> anyhow::Result<Vec<Requirement>> {
+        let mut reqs = Vec::new();
+        match self.language {
+            Language::Python => {
+                if let Some(cfg) = PythonConfig::load(&self.dirpath)? {
+                    for req in cfg.requirements() {
+                        if let Some((name, spec)) = python::requirement_specifier(req) {
+                            reqs.push(Requirement {
+                                name,
+                                version: spec.to_owned(),
+                            });
+                        }
+                    }
+                }
+            }
+            Language::Rust => {
+                let packages = self.cargo_packages()?;
+                for pkg in packages {
+                    for dep in pkg.dependencies {
+                        if dep.source.as_ref().is_some_and(Source::is_crates_io) {
+                            reqs.push(Requirement {
+                                name: dep.name,
+                                version: dep.req.to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        reqs.sort();
+        reqs.dedup();
+        Ok(reqs)
+    }
+
+    /// Compute the edits to the project's manifests needed to update the
+    /// requirements on the package `name` for the version `version`.  For
+    /// Rust projects, `version` is used as the new version requirement in the
+    /// `Cargo.toml` files of the project and all of its workspace members.
+    /// For Python projects, the requirements in `pyproject.toml` are updated.
+    /// The edits are not written to disk until [`DependencyBump::save()`] is
+    /// called.
+    pub(crate) fn bump_dependency(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> anyhow::Result<DependencyBump> {
+        let mut bump = DependencyBump::default();
+        match self.language {
+            Language::Python => {
+                let relpath = Path::new("pyproject.toml");
+                if self.dirpath.join(relpath).fs_err_try_exists()? {
+                    bump.edit(&self.dirpath, relpath, |doc| {
+                        bump::bump_pyproject(doc, name, version)
+                    })?;
+                }
+            }
+            Language::Rust => {
+                let mut manifests = vec![PathBuf::from("Cargo.toml")];
+                for m in self.members()? {
+                    if m.dirpath != Path::new(".") {
+                        manifests.push(m.dirpath.join("Cargo.toml"));
+                    }
+                }
+                for relpath in manifests {
+                    bump.edit(&self.dirpath, &relpath, |doc| {
+                        bump::bump_cargo_toml(doc, name, version)
+                    })?;
+                }
+            }
+        }
+        Ok(bump)
+    }
+
     pub(crate) fn source_paths(&self) -> anyhow::Result<Vec<PathBuf>> {
         match self.language {
             Language::Python => {
-                if self.dirpath.join("src").fs_err_try_exists()? {
+                let declared = PythonConfig::load(&self.dirpath)?
+                    .map(|cfg| cfg.source_paths())
+                    .unwrap_or_default();
+                if !declared.is_empty() {
+                    Ok(declared)
+                } else if self.dirpath.join("src").fs_err_try_exists()? {
                     Ok(vec![PathBuf::from("src")])
                 } else {
                     let mut srcs = Vec::new();
@@ -135,12 +456,7 @@ impl Project {
                 }
             }
             Language::Rust => {
-                let packages = MetadataCommand::new()
-                    .manifest_path(self.dirpath.join("Cargo.toml"))
-                    .no_deps()
-                    .exec()
-                    .context("failed to get project metadata")?
-                    .packages;
+                let packages = self.cargo_packages()?;
                 let mut srcs = HashSet::new();
                 for p in packages {
                     for t in p.targets {
@@ -173,13 +489,78 @@ impl Project {
             == Some(1))
     }
 
-    pub(crate) fn stash(&self) -> anyhow::Result<()> {
+    /// Test whether the project has any uncommitted changes or untracked
+    /// files
+    pub(crate) fn is_dirty(&self) -> anyhow::Result<bool> {
         // TODO: Should --ignore-submodules be set to something?
-        if !self
+        Ok(!self
             .readcmd("git", ["status", "--porcelain", "-unormal"])?
-            .is_empty()
-        {
+            .is_empty())
+    }
+
+    /// Stash any uncommitted changes.  If a stash was created, its commit ID
+    /// is returned.
+    pub(crate) fn stash(&self) -> anyhow::Result<Option<String>> {
+        if self.is_dirty()? {
             self.runcmd("git").args(["stash", "-u"]).run()?;
+            Ok(Some(self.readcmd("git", ["rev-parse", "refs/stash"])?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Pop the stash entry with the given commit ID, wherever it is in the
+    /// stash list
+    pub(crate) fn pop_stash(&self, oid: &str) -> anyhow::Result<()> {
+        let Some(index) = self
+            .readcmd("git", ["stash", "list", "--format=%H"])?
+            .lines()
+            .position(|ln| ln == oid)
+        else {
+            anyhow::bail!("Stash {oid} no longer exists in {}", self.name());
+        };
+        self.runcmd("git")
+            .args(["stash", "pop"])
+            .arg(format!("stash@{{{index}}}"))
+            .run()?;
+        Ok(())
+    }
+
+    /// Return the currently checked-out branch or, if `HEAD` is detached, the
+    /// current commit
+    pub(crate) fn head(&self) -> anyhow::Result<Head> {
+        match self.current_branch()? {
+            Some(branch) => Ok(Head::Branch(branch)),
+            None => Ok(Head::Detached(self.readcmd("git", ["rev-parse", "HEAD"])?)),
+        }
+    }
+
+    /// Record the current `HEAD` and stash any uncommitted changes so that
+    /// they can later be restored with [`Project::restore()`]
+    pub(crate) fn checkpoint(&self) -> anyhow::Result<Checkpoint> {
+        let head = self.head()?;
+        let stash = self.stash()?;
+        Ok(Checkpoint { head, stash })
+    }
+
+    /// Check out the `HEAD` recorded in `checkpoint` and pop the stash it
+    /// created, if any
+    pub(crate) fn restore(&self, checkpoint: &Checkpoint) -> anyhow::Result<()> {
+        if self.head()? != checkpoint.head {
+            match checkpoint.head {
+                Head::Branch(ref branch) => {
+                    self.runcmd("git").arg("checkout").arg(branch).run()?;
+                }
+                Head::Detached(ref oid) => {
+                    self.runcmd("git")
+                        .args(["checkout", "--detach"])
+                        .arg(oid)
+                        .run()?;
+                }
+            }
+        }
+        if let Some(ref oid) = checkpoint.stash {
+            self.pop_stash(oid)?;
         }
         Ok(())
     }
@@ -227,6 +608,20 @@ impl Project {
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Head {
+    Branch(String),
+    Detached(String),
+}
+
+/// A project's `HEAD` and the commit ID of the stash (if any) created when
+/// the checkpoint was made
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Checkpoint {
+    head: Head,
+    stash: Option<String>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub(crate) struct ProjectDetails {
     pub(crate) name: String,
@@ -236,22 +631,48 @@ pub(crate) struct ProjectDetails {
     pub(crate) on_default_branch: bool,
     pub(crate) is_workspace: bool,
     pub(crate) is_virtual_workspace: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) members: Option<Vec<WorkspaceMember>>,
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-struct Pyproject {
-    project: NameTable,
+/// The packages provided by a project and the packages it depends on, as
+/// normalized names (lowercased, with runs of `-`, `_`, and `.` replaced by
+/// `-`)
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct PackageDeps {
+    pub(crate) provides: Vec<String>,
+    pub(crate) requires: Vec<String>,
+}
+
+/// A requirement on a package from a package registry
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) struct Requirement {
+    /// The name of the package, normalized for Python packages
+    pub(crate) name: String,
+
+    /// The version requirement (for Rust) or specifier (for Python)
+    pub(crate) version: String,
+}
+
+/// A package in a Rust workspace
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct WorkspaceMember {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    /// The path to the member's directory, relative to the project's
+    /// directory
+    pub(crate) dirpath: PathBuf,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 #[serde(try_from = "RawCargo")]
 enum Cargo {
     Package {
-        package: NameTable,
+        package: Package,
     },
     Workspace {
         workspace: Workspace,
-        package: NameTable,
+        package: Package,
     },
     Virtual {
         workspace: Workspace,
@@ -259,11 +680,40 @@ enum Cargo {
 }
 
 impl Cargo {
-    fn name(&self) -> &str {
+    /// Return the project name.  For virtual workspaces, this is the name of
+    /// the repository in `workspace.package.repository`, or `None` if that is
+    /// not set.
+    fn name(&self) -> Option<&str> {
+        match self {
+            Cargo::Workspace { package, .. } => Some(&package.name),
+            Cargo::Virtual { workspace } => workspace
+                .package
+                .as_ref()?
+                .repository
+                .as_deref()
+                .and_then(repo_name_from_url),
+            Cargo::Package { package } => Some(&package.name),
+        }
+    }
+
+    /// Return the package version, resolving `version.workspace = true`
+    /// against the `[workspace.package]` table.  For virtual workspaces, the
+    /// version in `[workspace.package]` (if any) is returned.
+    fn version(&self) -> Option<&str> {
         match self {
-            Cargo::Workspace { package, .. } => &package.name,
-            Cargo::Virtual { workspace } => workspace.package.repository.name(),
-            Cargo::Package { package } => &package.name,
+            Cargo::Package { package } => match package.version.as_ref()? {
+                PackageVersion::Literal(v) => Some(v),
+                // Cannot be resolved without the parent workspace
+                PackageVersion::Inherited { .. } => None,
+            },
+            Cargo::Workspace { workspace, package } => match package.version.as_ref()? {
+                PackageVersion::Literal(v) => Some(v),
+                PackageVersion::Inherited { workspace: true } => {
+                    workspace.package.as_ref()?.version.as_deref()
+                }
+                PackageVersion::Inherited { workspace: false } => None,
+            },
+            Cargo::Virtual { workspace } => workspace.package.as_ref()?.version.as_deref(),
         }
     }
 
@@ -306,22 +756,199 @@ impl TryFrom<RawCargo> for Cargo {
 pub(crate) struct FromRawCargoError;
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-struct NameTable {
+struct Package {
     name: String,
+    version: Option<PackageVersion>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(untagged)]
+enum PackageVersion {
+    Literal(String),
+    Inherited { workspace: bool },
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 struct RawCargo {
-    package: Option<NameTable>,
+    package: Option<Package>,
     workspace: Option<Workspace>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 struct Workspace {
-    package: WorkspacePackage,
+    package: Option<WorkspacePackage>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 struct WorkspacePackage {
-    repository: GHRepo,
+    repository: Option<String>,
+    version: Option<String>,
+}
+
+/// Normalize a package name by lowercasing it and replacing runs of `-`, `_`,
+/// and `.` with a single `-`, as per PEP 503
+pub(crate) fn normalize_name(name: &str) -> String {
+    let mut normed = String::with_capacity(name.len());
+    let mut prev_sep = false;
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !prev_sep {
+                normed.push('-');
+            }
+            prev_sep = true;
+        } else {
+            normed.push(c.to_ascii_lowercase());
+            prev_sep = false;
+        }
+    }
+    normed
+}
+
+/// Return the final path component of a repository URL with any `.git`
+/// suffix removed
+fn repo_name_from_url(url: &str) -> Option<&str> {
+    let url = url.trim_end_matches('/');
+    let name = url.rsplit_once('/').map_or(url, |(_, name)| name);
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    (!name.is_empty()).then_some(name)
+}
+
+fn dirname(path: &Path) -> anyhow::Result<String> {
+    let path = fs_err::canonicalize(path)?;
+    let Some(name) = path.file_name() else {
+        anyhow::bail!("Could not determine directory name of {}", path.display());
+    };
+    Ok(name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cargo_version() {
+        let cargo = toml::from_str::<Cargo>(concat!(
+            "[package]\n",
+            "name = \"foo\"\n",
+            "version = \"1.2.3\"\n",
+        ))
+        .unwrap();
+        assert_eq!(cargo.version(), Some("1.2.3"));
+    }
+
+    #[test]
+    fn test_cargo_inherited_version() {
+        let cargo = toml::from_str::<Cargo>(concat!(
+            "[workspace]\n",
+            "members = [\"bar\"]\n",
+            "\n",
+            "[workspace.package]\n",
+            "repository = \"https://github.com/octocat/foo\"\n",
+            "version = \"0.4.0\"\n",
+            "\n",
+            "[package]\n",
+            "name = \"foo\"\n",
+            "version.workspace = true\n",
+        ))
+        .unwrap();
+        assert_eq!(cargo.version(), Some("0.4.0"));
+    }
+
+    #[test]
+    fn test_virtual_workspace_name() {
+        let cargo = toml::from_str::<Cargo>(concat!(
+            "[workspace]\n",
+            "members = [\"crates/*\"]\n",
+            "\n",
+            "[workspace.package]\n",
+            "repository = \"https://gitlab.com/octocat/my-workspace.git\"\n",
+        ))
+        .unwrap();
+        assert_eq!(cargo.name(), Some("my-workspace"));
+        assert!(cargo.is_virtual_workspace());
+    }
+
+    #[test]
+    fn test_virtual_workspace_no_repository() {
+        let cargo =
+            toml::from_str::<Cargo>(concat!("[workspace]\n", "members = [\"crates/*\"]\n",))
+                .unwrap();
+        assert_eq!(cargo.name(), None);
+        assert_eq!(cargo.version(), None);
+    }
+
+    #[test]
+    fn test_cargo_no_version() {
+        let cargo = toml::from_str::<Cargo>(concat!(
+            "[package]\n",
+            "name = \"foo\"\n",
+            "publish = false\n",
+        ))
+        .unwrap();
+        assert_eq!(cargo.version(), None);
+    }
+
+    /// Create a Git repository in a temporary directory containing a Rust
+    /// package, and return the directory along with the `Project` for it
+    fn git_project() -> (tempfile::TempDir, Project) {
+        let tmpdir = tempfile::tempdir().unwrap();
+        fs_err::write(
+            tmpdir.path().join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        git(tmpdir.path(), &["init", "-b", "main"]);
+        git(tmpdir.path(), &["add", "Cargo.toml"]);
+        git(tmpdir.path(), &["commit", "-m", "Initial commit"]);
+        let p = Project::try_for_dirpath(tmpdir.path().to_owned())
+            .unwrap()
+            .unwrap();
+        (tmpdir, p)
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        CommandPlus::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .run()
+            .unwrap();
+    }
+
+    /// Create a branch `name` off of the current branch containing one commit
+    /// and then check the current branch back out
+    fn make_branch(p: &Project, name: &str) {
+        git(p.dirpath(), &["checkout", "-b", name]);
+        fs_err::write(p.dirpath().join(format!("{name}.txt")), "text\n").unwrap();
+        git(p.dirpath(), &["add", "."]);
+        git(p.dirpath(), &["commit", "-m", &format!("Add {name}.txt")]);
+        git(p.dirpath(), &["checkout", "-"]);
+    }
+
+    #[test]
+    fn test_delete_merged_local_branch() {
+        let (_tmpdir, p) = git_project();
+        make_branch(&p, "feature");
+        git(
+            p.dirpath(),
+            &["merge", "--no-ff", "-m", "Merge feature", "feature"],
+        );
+        p.delete_local_branch("feature", false).unwrap();
+        assert_eq!(p.local_branches().unwrap(), ["main"]);
+    }
+
+    #[test]
+    fn test_delete_squash_merged_local_branch() {
+        let (_tmpdir, p) = git_project();
+        make_branch(&p, "feature");
+        git(p.dirpath(), &["merge", "--squash", "feature"]);
+        git(p.dirpath(), &["commit", "-m", "Squash-merge feature"]);
+        git(p.dirpath(), &["checkout", "feature"]);
+        p.delete_local_branch("feature", false).unwrap();
+        assert_eq!(p.local_branches().unwrap(), ["feature", "main"]);
+        assert_eq!(p.current_branch().unwrap().as_deref(), Some("feature"));
+        p.delete_local_branch("feature", true).unwrap();
+        assert_eq!(p.local_branches().unwrap(), ["main"]);
+        assert_eq!(p.current_branch().unwrap().as_deref(), Some("main"));
+    }
 }
@@ -0,0 +1,634 @@
+use super::normalize_name;
+use anyhow::Context;
+use fs_err::PathExt;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// The packaging configuration of a Python project, gathered from
+/// `pyproject.toml`, `setup.cfg`, and `setup.py`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct PythonConfig {
+    pyproject: Option<Pyproject>,
+    setup_cfg: Option<SetupCfg>,
+    setup_py: Option<String>,
+}
+
+impl PythonConfig {
+    /// Read the packaging configuration files in `dirpath`.  Returns `None`
+    /// if none of the files are present.
+    pub(super) fn load(dirpath: &Path) -> anyhow::Result<Option<PythonConfig>> {
+        let mut config = PythonConfig::default();
+        let path = dirpath.join("pyproject.toml");
+        if path.fs_err_try_exists()? {
+            let src = fs_err::read_to_string(&path)?;
+            config.pyproject = Some(
+                toml::from_str::<Pyproject>(&src)
+                    .context("failed to deserialize pyproject.toml")?,
+            );
+        }
+        let path = dirpath.join("setup.cfg");
+        if path.fs_err_try_exists()? {
+            config.setup_cfg = Some(SetupCfg::parse(&fs_err::read_to_string(&path)?));
+        }
+        let path = dirpath.join("setup.py");
+        if path.fs_err_try_exists()? {
+            config.setup_py = Some(fs_err::read_to_string(&path)?);
+        }
+        if config == PythonConfig::default() {
+            Ok(None)
+        } else {
+            Ok(Some(config))
+        }
+    }
+
+    /// Returns true if the configuration declares a Python package, as
+    /// opposed to merely configuring Python tools (as a `pyproject.toml` or
+    /// `setup.cfg` in a non-Python project might)
+    pub(super) fn declares_package(&self) -> bool {
+        self.pyproject
+            .as_ref()
+            .is_some_and(|pp| pp.project.is_some() || pp.poetry().is_some())
+            || self
+                .setup_cfg
+                .as_ref()
+                .is_some_and(|cfg| cfg.has_section("metadata") || cfg.has_section("options"))
+            || self.setup_py.is_some()
+    }
+
+    /// Return the project name from the first of `[project]`,
+    /// `[tool.poetry]`, `setup.cfg`, or `setup.py` that declares one
+    pub(super) fn name(&self) -> Option<String> {
+        self.pyproject
+            .as_ref()
+            .and_then(|pp| pp.project.as_ref()?.name.clone())
+            .or_else(|| self.pyproject.as_ref()?.poetry()?.name.clone())
+            .or_else(|| {
+                self.setup_cfg
+                    .as_ref()?
+                    .get("metadata", "name")
+                    .map(ToOwned::to_owned)
+            })
+            .or_else(|| setup_py_kwarg(self.setup_py.as_deref()?, "name"))
+    }
+
+    /// Return the project version from the first of `[project]`,
+    /// `[tool.poetry]`, `setup.cfg`, or `setup.py` that declares a literal
+    /// version
+    pub(super) fn version(&self) -> Option<String> {
+        self.pyproject
+            .as_ref()
+            .and_then(|pp| pp.project.as_ref()?.version.clone())
+            .or_else(|| self.pyproject.as_ref()?.poetry()?.version.clone())
+            .or_else(|| {
+                // Skip directives like "attr: foo.__version__"
+                self.setup_cfg
+                    .as_ref()?
+                    .get("metadata", "version")
+                    .filter(|v| !v.contains(':'))
+                    .map(ToOwned::to_owned)
+            })
+            .or_else(|| setup_py_kwarg(self.setup_py.as_deref()?, "version"))
+    }
+
+    /// Return the normalized names of the packages that the project depends
+    /// on, including optional and development dependencies
+    pub(super) fn dependencies(&self) -> Vec<String> {
+        let mut names = self
+            .requirements()
+            .into_iter()
+            .filter_map(requirement_name)
+            .collect::<Vec<_>>();
+        if let Some(poetry) = self.pyproject.as_ref().and_then(Pyproject::poetry) {
+            let tables = [&poetry.dependencies, &poetry.dev_dependencies]
+                .into_iter()
+                .chain(poetry.group.values().map(|g| &g.dependencies));
+            for table in tables {
+                // Poetry lists Python itself as a dependency
+                names.extend(
+                    table
+                        .keys()
+                        .filter(|&k| k != "python")
+                        .map(|k| normalize_name(k)),
+                );
+            }
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Return the PEP 508 requirement strings for the project's
+    /// dependencies, including optional and development dependencies.
+    /// Dependencies declared in Poetry's tables are not included, as they do
+    /// not use PEP 508 syntax.
+    pub(super) fn requirements(&self) -> Vec<&str> {
+        let mut reqs = Vec::new();
+        if let Some(ref pp) = self.pyproject {
+            if let Some(ref project) = pp.project {
+                reqs.extend(project.dependencies.iter().map(String::as_str));
+                reqs.extend(
+                    project
+                        .optional_dependencies
+                        .values()
+                        .flatten()
+                        .map(String::as_str),
+                );
+            }
+            if let Some(ref groups) = pp.dependency_groups {
+                reqs.extend(groups.values().flatten().filter_map(toml::Value::as_str));
+            }
+        }
+        if let Some(ref cfg) = self.setup_cfg {
+            if let Some(install) = cfg.get("options", "install_requires") {
+                reqs.extend(cfg_list_lines(install));
+            }
+            if let Some(extras) = cfg.0.get("options.extras_require") {
+                reqs.extend(extras.values().flat_map(|v| cfg_list_lines(v)));
+            }
+        }
+        reqs
+    }
+
+    /// Return the paths (relative to the project directory) of the packages
+    /// and modules declared in the setuptools, Poetry, or Hatch
+    /// configuration.  Returns an empty list if no such configuration is
+    /// present.
+    pub(super) fn source_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Some(ref pp) = self.pyproject {
+            if let Some(st) = pp.tool.as_ref().and_then(|t| t.setuptools.as_ref()) {
+                let root = st.package_dir.get("").map_or(".", String::as_str);
+                match st.packages {
+                    Some(SetuptoolsPackages::List(ref pkgs)) => {
+                        paths.extend(pkgs.iter().map(|pkg| package_path(root, pkg)));
+                    }
+                    Some(SetuptoolsPackages::Find { ref find }) => {
+                        paths.extend(find.where_.iter().map(PathBuf::from));
+                    }
+                    None if root != "." => paths.push(PathBuf::from(root)),
+                    None => (),
+                }
+                paths.extend(st.py_modules.iter().map(|m| module_path(root, m)));
+            }
+            if let Some(poetry) = pp.poetry() {
+                for pkg in &poetry.packages {
+                    let from = pkg.from.as_deref().unwrap_or(".");
+                    paths.push(Path::new(from).join(&pkg.include));
+                }
+            }
+            if let Some(wheel) = pp.hatch_wheel() {
+                paths.extend(wheel.packages.iter().map(PathBuf::from));
+            }
+        }
+        if let Some(ref cfg) = self.setup_cfg {
+            let root = cfg
+                .get("options", "package_dir")
+                .and_then(setup_cfg_root_dir)
+                .unwrap_or(".");
+            match cfg.get("options", "packages") {
+                Some(pkgs) if pkgs.starts_with("find") => {
+                    match cfg.get("options.packages.find", "where") {
+                        Some(wh) => paths.extend(cfg_list(wh).map(PathBuf::from)),
+                        None if root != "." => paths.push(PathBuf::from(root)),
+                        None => (),
+                    }
+                }
+                Some(pkgs) => paths.extend(cfg_list(pkgs).map(|pkg| package_path(root, pkg))),
+                None => (),
+            }
+            if let Some(mods) = cfg.get("options", "py_modules") {
+                paths.extend(cfg_list(mods).map(|m| module_path(root, m)));
+            }
+        }
+        let mut normed = Vec::with_capacity(paths.len());
+        for p in paths {
+            let p = p
+                .strip_prefix(".")
+                .map_or_else(|_| p.clone(), Path::to_path_buf);
+            if !p.as_os_str().is_empty() && !normed.contains(&p) {
+                normed.push(p);
+            }
+        }
+        normed
+    }
+}
+
+/// Return the path to the directory for the top-level package of the
+/// (possibly dotted) package name `pkg`
+fn package_path(root: &str, pkg: &str) -> PathBuf {
+    let top = pkg.split_once('.').map_or(pkg, |(top, _)| top);
+    Path::new(root).join(top)
+}
+
+fn module_path(root: &str, module: &str) -> PathBuf {
+    Path::new(root).join(format!("{}.py", module.replace('.', "/")))
+}
+
+/// Split a `setup.cfg` list value, which may be separated by commas and/or
+/// newlines
+fn cfg_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// Given the `package_dir` option from `setup.cfg`, return the directory
+/// mapped to the root package, if any
+fn setup_cfg_root_dir(value: &str) -> Option<&str> {
+    cfg_list(value).find_map(|entry| {
+        let (pkg, dir) = entry.split_once('=')?;
+        pkg.trim().is_empty().then(|| dir.trim())
+    })
+}
+
+/// Split a `setup.cfg` list of requirements, which are separated by newlines
+/// (as commas and semicolons can appear within requirements), and strip any
+/// `#` comments
+fn cfg_list_lines(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .lines()
+        .map(|line| line.split_once(" #").map_or(line, |(req, _)| req).trim())
+        .filter(|s| !s.is_empty() && !s.starts_with('#'))
+}
+
+/// Return the normalized project name from a PEP 508 requirement string
+pub(super) fn requirement_name(req: &str) -> Option<String> {
+    let name = split_requirement(req).0.trim_start();
+    (!name.is_empty()).then(|| normalize_name(name))
+}
+
+/// Split a PEP 508 requirement string into the normalized project name and
+/// the version specifier (without any enclosing parentheses).  Returns `None`
+/// for URL requirements.
+pub(super) fn requirement_specifier(req: &str) -> Option<(String, &str)> {
+    let (name, rest) = split_requirement(req);
+    let name = name.trim_start();
+    if name.is_empty() {
+        return None;
+    }
+    let rest = rest.split(';').next().unwrap_or_default().trim();
+    let spec = match rest.strip_prefix('[') {
+        Some(extras) => extras.split_once(']')?.1.trim(),
+        None => rest,
+    };
+    if spec.starts_with('@') {
+        return None;
+    }
+    let spec = spec
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .unwrap_or(spec)
+        .trim();
+    Some((normalize_name(name), spec))
+}
+
+/// Split a PEP 508 requirement string into the project name (with any
+/// leading whitespace) and the rest of the requirement
+pub(super) fn split_requirement(req: &str) -> (&str, &str) {
+    let start = req.len() - req.trim_start().len();
+    let end = req[start..]
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .map_or(req.len(), |i| start + i);
+    req.split_at(end)
+}
+
+/// Extract the value of a string literal keyword argument (e.g.,
+/// `name="foo"`) from the source of a `setup.py` file
+fn setup_py_kwarg(src: &str, key: &str) -> Option<String> {
+    let mut start = 0;
+    while let Some(i) = src[start..].find(key) {
+        let at = start + i;
+        start = at + key.len();
+        if src[..at]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        {
+            continue;
+        }
+        let Some(rest) = src[start..].trim_start().strip_prefix('=') else {
+            continue;
+        };
+        if rest.starts_with('=') {
+            continue;
+        }
+        let rest = rest.trim_start();
+        let Some(quote) = rest.chars().next().filter(|&c| c == '"' || c == '\'') else {
+            continue;
+        };
+        let value = &rest[1..];
+        if let Some(end) = value.find(quote) {
+            return Some(value[..end].to_owned());
+        }
+    }
+    None
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Pyproject {
+    project: Option<PyprojectProject>,
+    tool: Option<Tool>,
+    // Values are requirement strings or `{include-group = "..."}` tables
+    dependency_groups: Option<BTreeMap<String, Vec<toml::Value>>>,
+}
+
+impl Pyproject {
+    fn poetry(&self) -> Option<&Poetry> {
+        self.tool.as_ref()?.poetry.as_ref()
+    }
+
+    fn hatch_wheel(&self) -> Option<&HatchWheel> {
+        self.tool
+            .as_ref()?
+            .hatch
+            .as_ref()?
+            .build
+            .as_ref()?
+            .targets
+            .as_ref()?
+            .wheel
+            .as_ref()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct PyprojectProject {
+    // Absent if the name is provided by another build backend configuration
+    name: Option<String>,
+    // Absent if the version is declared as dynamic
+    version: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+    #[serde(default)]
+    optional_dependencies: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct Tool {
+    poetry: Option<Poetry>,
+    setuptools: Option<Setuptools>,
+    hatch: Option<Hatch>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Poetry {
+    name: Option<String>,
+    version: Option<String>,
+    #[serde(default)]
+    packages: Vec<PoetryPackage>,
+    // Only the keys of the dependency tables are needed
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    dev_dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    group: BTreeMap<String, PoetryGroup>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct PoetryGroup {
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct PoetryPackage {
+    include: String,
+    from: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Setuptools {
+    packages: Option<SetuptoolsPackages>,
+    #[serde(default)]
+    package_dir: BTreeMap<String, String>,
+    #[serde(default)]
+    py_modules: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(untagged)]
+enum SetuptoolsPackages {
+    List(Vec<String>),
+    Find { find: PackagesFind },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct PackagesFind {
+    #[serde(default, rename = "where")]
+    where_: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct Hatch {
+    build: Option<HatchBuild>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct HatchBuild {
+    targets: Option<HatchTargets>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct HatchTargets {
+    wheel: Option<HatchWheel>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct HatchWheel {
+    #[serde(default)]
+    packages: Vec<String>,
+}
+
+/// A minimal parser for the INI format used by `setup.cfg`.  Section and key
+/// names are lowercased, and indented continuation lines are appended to the
+/// preceding value.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct SetupCfg(HashMap<String, HashMap<String, String>>);
+
+impl SetupCfg {
+    fn parse(src: &str) -> SetupCfg {
+        let mut sections = HashMap::<String, HashMap<String, String>>::new();
+        let mut section = None;
+        let mut key = None;
+        for line in src.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with(['#', ';']) {
+                continue;
+            }
+            if line.starts_with(char::is_whitespace) {
+                if let (Some(sect), Some(k)) = (section.as_ref(), key.as_ref())
+                    && let Some(value) = sections.get_mut(sect).and_then(|s| s.get_mut(k))
+                {
+                    if !value.is_empty() {
+                        value.push('\n');
+                    }
+                    value.push_str(trimmed);
+                }
+            } else if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                let name = name.trim().to_ascii_lowercase();
+                sections.entry(name.clone()).or_default();
+                section = Some(name);
+                key = None;
+            } else if let Some(sect) = section.as_ref()
+                && let Some(i) = trimmed.find(['=', ':'])
+            {
+                let k = trimmed[..i].trim().to_ascii_lowercase();
+                let v = trimmed[i + 1..].trim().to_owned();
+                sections
+                    .entry(sect.clone())
+                    .or_default()
+                    .insert(k.clone(), v);
+                key = Some(k);
+            }
+        }
+        SetupCfg(sections)
+    }
+
+    fn has_section(&self, section: &str) -> bool {
+        self.0.contains_key(section)
+    }
+
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.0.get(section)?.get(key).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pyproject(src: &str) -> PythonConfig {
+        PythonConfig {
+            pyproject: Some(toml::from_str(src).unwrap()),
+            ..PythonConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_poetry() {
+        let config = pyproject(concat!(
+            "[tool.poetry]\n",
+            "name = \"foo\"\n",
+            "version = \"1.2.3\"\n",
+            "packages = [{ include = \"foo\", from = \"src\" }]\n",
+        ));
+        assert!(config.declares_package());
+        assert_eq!(config.name().as_deref(), Some("foo"));
+        assert_eq!(config.version().as_deref(), Some("1.2.3"));
+        assert_eq!(config.source_paths(), [PathBuf::from("src/foo")]);
+    }
+
+    #[test]
+    fn test_setuptools_pyproject() {
+        let config = pyproject(concat!(
+            "[project]\n",
+            "name = \"foo\"\n",
+            "dynamic = [\"version\"]\n",
+            "\n",
+            "[tool.setuptools]\n",
+            "package-dir = { \"\" = \"lib\" }\n",
+            "packages = [\"foo\", \"foo.bar\"]\n",
+            "py-modules = [\"quux\"]\n",
+        ));
+        assert_eq!(config.name().as_deref(), Some("foo"));
+        assert_eq!(config.version(), None);
+        assert_eq!(
+            config.source_paths(),
+            [PathBuf::from("lib/foo"), PathBuf::from("lib/quux.py")]
+        );
+    }
+
+    #[test]
+    fn test_tool_config_only() {
+        let config = pyproject(concat!("[tool.black]\n", "line-length = 88\n"));
+        assert!(!config.declares_package());
+        assert_eq!(config.name(), None);
+        assert!(config.source_paths().is_empty());
+    }
+
+    #[test]
+    fn test_setup_cfg() {
+        let config = PythonConfig {
+            setup_cfg: Some(SetupCfg::parse(concat!(
+                "[metadata]\n",
+                "name = foo-bar\n",
+                "version = attr: foo_bar.__version__\n",
+                "\n",
+                "[options]\n",
+                "package_dir =\n",
+                "    =src\n",
+                "packages = find:\n",
+                "\n",
+                "[options.packages.find]\n",
+                "where = src\n",
+                "\n",
+                "[flake8]\n",
+                "max-line-length = 100\n",
+            ))),
+            ..PythonConfig::default()
+        };
+        assert!(config.declares_package());
+        assert_eq!(config.name().as_deref(), Some("foo-bar"));
+        assert_eq!(config.version(), None);
+        assert_eq!(config.source_paths(), [PathBuf::from("src")]);
+    }
+
+    #[test]
+    fn test_setup_cfg_requirements() {
+        let config = PythonConfig {
+            setup_cfg: Some(SetupCfg::parse(concat!(
+                "[options]\n",
+                "install_requires =\n",
+                "    attrs >= 21.3\n",
+                "    foo; python_version<\"3.8\"\n",
+                "    # A comment\n",
+                "    bar[baz] ~= 1.0  # Another comment\n",
+                "\n",
+                "[options.extras_require]\n",
+                "test = pytest; sys_platform != \"win32\"\n",
+            ))),
+            ..PythonConfig::default()
+        };
+        assert_eq!(
+            config.requirements(),
+            [
+                "attrs >= 21.3",
+                "foo; python_version<\"3.8\"",
+                "bar[baz] ~= 1.0",
+                "pytest; sys_platform != \"win32\"",
+            ]
+        );
+        assert_eq!(config.dependencies(), ["attrs", "bar", "foo", "pytest"]);
+    }
+
+    #[test]
+    fn test_setup_py() {
+        let config = PythonConfig {
+            setup_py: Some(String::from(concat!(
+                "from setuptools import setup\n",
+                "\n",
+                "setup(\n",
+                "    name = 'foo',\n",
+                "    version=\"0.1.0\",\n",
+                "    py_modules=[\"foo\"],\n",
+                ")\n",
+            ))),
+            ..PythonConfig::default()
+        };
+        assert_eq!(config.name().as_deref(), Some("foo"));
+        assert_eq!(config.version().as_deref(), Some("0.1.0"));
+    }
+
+    #[test]
+    fn test_setup_py_kwarg_skips_other_identifiers() {
+        let src = "pkg_name = compute()\nif name == 'x': pass\nsetup(name=\"real\")\n";
+        assert_eq!(setup_py_kwarg(src, "name").as_deref(), Some("real"));
+    }
+}
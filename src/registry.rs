@@ -0,0 +1,183 @@
+use crate::pep440;
+use crate::project::normalize_name;
+use anyhow::Context;
+use cargo_metadata::semver;
+use fs_err::PathExt;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// A local copy of a Cargo registry index (e.g., a mirror of the crates.io
+/// index) laid out as described in
+/// <https://doc.rust-lang.org/cargo/reference/registry-index.html#index-files>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CargoIndex {
+    root: PathBuf,
+}
+
+impl CargoIndex {
+    pub(crate) fn new(root: PathBuf) -> CargoIndex {
+        CargoIndex { root }
+    }
+
+    /// Return the newest non-yanked, non-prerelease version of the given
+    /// crate in the index.  Returns `None` if the crate is not in the index or
+    /// has no such versions.
+    pub(crate) fn latest_version(&self, name: &str) -> anyhow::Result<Option<semver::Version>> {
+        let path = self.root.join(cargo_index_path(name));
+        if !path.fs_err_try_exists()? {
+            return Ok(None);
+        }
+        let mut latest = None;
+        for line in fs_err::read_to_string(&path)?.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str::<IndexEntry>(line)
+                .with_context(|| format!("failed to parse entry in {}", path.display()))?;
+            if entry.yanked {
+                continue;
+            }
+            let Ok(v) = entry.vers.parse::<semver::Version>() else {
+                continue;
+            };
+            if v.pre.is_empty() && latest.as_ref().is_none_or(|l| v > *l) {
+                latest = Some(v);
+            }
+        }
+        Ok(latest)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Return the path of the file for the given crate relative to the root of a
+/// Cargo registry index
+fn cargo_index_path(name: &str) -> PathBuf {
+    let name = name.to_ascii_lowercase();
+    match name.len() {
+        0..=2 => Path::new(&name.len().to_string()).join(&name),
+        3 => Path::new("3").join(&name[..1]).join(&name),
+        _ => Path::new(&name[..2]).join(&name[2..4]).join(&name),
+    }
+}
+
+/// A local Python package repository laid out as a [PEP 503][] "simple"
+/// repository, i.e., a directory containing a subdirectory for each project
+/// (named after the normalized project name) that contains either the
+/// project's distribution files or an `index.html` file linking to them
+///
+/// [PEP 503]: https://peps.python.org/pep-0503/
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct SimpleIndex {
+    root: PathBuf,
+}
+
+impl SimpleIndex {
+    pub(crate) fn new(root: PathBuf) -> SimpleIndex {
+        SimpleIndex { root }
+    }
+
+    /// Return the newest non-yanked, non-prerelease version of the given
+    /// project in the repository.  Returns `None` if the project is not in
+    /// the repository or has no such versions.
+    pub(crate) fn latest_version(&self, name: &str) -> anyhow::Result<Option<pep440::Version>> {
+        let dirpath = self.root.join(normalize_name(name));
+        if !dirpath.fs_err_try_exists()? {
+            return Ok(None);
+        }
+        let index = dirpath.join("index.html");
+        let filenames = if index.fs_err_try_exists()? {
+            index_filenames(&fs_err::read_to_string(index)?)
+        } else {
+            let mut filenames = Vec::new();
+            for entry in fs_err::read_dir(&dirpath)? {
+                filenames.push(entry?.file_name().to_string_lossy().into_owned());
+            }
+            filenames
+        };
+        Ok(filenames
+            .iter()
+            .filter_map(|f| dist_version(f)?.parse::<pep440::Version>().ok())
+            .filter(|v| !v.is_prerelease())
+            .max())
+    }
+}
+
+/// Return the text of the non-yanked links in a PEP 503 project page
+fn index_filenames(html: &str) -> Vec<String> {
+    let mut filenames = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<a ") {
+        rest = &rest[start..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..tag_end];
+        rest = &rest[tag_end + 1..];
+        let Some(text_end) = rest.find("</a>") else {
+            break;
+        };
+        if !tag.contains("data-yanked") {
+            filenames.push(rest[..text_end].trim().to_owned());
+        }
+        rest = &rest[text_end..];
+    }
+    filenames
+}
+
+/// Extract the version from the filename of a wheel or sdist
+fn dist_version(filename: &str) -> Option<&str> {
+    if let Some(stem) = filename.strip_suffix(".whl") {
+        return stem.split('-').nth(1);
+    }
+    [".tar.gz", ".zip", ".tar.bz2", ".tgz"]
+        .into_iter()
+        .find_map(|ext| filename.strip_suffix(ext))?
+        .rsplit_once('-')
+        .map(|(_, v)| v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("a", "1/a")]
+    #[case("cc", "2/cc")]
+    #[case("Syn", "3/s/syn")]
+    #[case("serde_json", "se/rd/serde_json")]
+    fn test_cargo_index_path(#[case] name: &str, #[case] path: &str) {
+        assert_eq!(cargo_index_path(name), Path::new(path));
+    }
+
+    #[test]
+    fn test_index_filenames() {
+        let html = concat!(
+            "<!DOCTYPE html>\n",
+            "<html><body>\n",
+            "<a href=\"../../files/foo-1.0.tar.gz#sha256=abc\">foo-1.0.tar.gz</a><br/>\n",
+            "<a href=\"foo-1.1-py3-none-any.whl\" data-yanked=\"\">foo-1.1-py3-none-any.whl</a>\n",
+            "<a href=\"foo_bar-2.0-py3-none-any.whl\">\n  foo_bar-2.0-py3-none-any.whl\n</a>\n",
+            "</body></html>\n",
+        );
+        assert_eq!(
+            index_filenames(html),
+            ["foo-1.0.tar.gz", "foo_bar-2.0-py3-none-any.whl"]
+        );
+    }
+
+    #[rstest]
+    #[case("foo-1.0.tar.gz", Some("1.0"))]
+    #[case("foo-bar-1.0rc1.zip", Some("1.0rc1"))]
+    #[case("foo_bar-2.0-py3-none-any.whl", Some("2.0"))]
+    #[case("foo-1.0.exe", None)]
+    fn test_dist_version(#[case] filename: &str, #[case] version: Option<&str>) {
+        assert_eq!(dist_version(filename), version);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::util::xdg_dir;
+use anyhow::Context;
+use ghrepo::GHRepo;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// A record of a pull request created by `run-pr`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct SweepRecord {
+    /// The name of the PR branch, which identifies the sweep
+    pub(crate) branch: String,
+    pub(crate) project: String,
+    pub(crate) repo: GHRepo,
+    pub(crate) number: u64,
+    pub(crate) html_url: String,
+}
+
+/// A newline-delimited JSON file of [`SweepRecord`]s
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct SweepLog {
+    path: PathBuf,
+}
+
+impl SweepLog {
+    pub(crate) fn new(path: PathBuf) -> SweepLog {
+        SweepLog { path }
+    }
+
+    /// Return the sweep log at the default location:
+    /// `$XDG_STATE_HOME/forall/sweeps.jsonl`, with `$XDG_STATE_HOME`
+    /// defaulting to `~/.local/state`
+    pub(crate) fn default_location() -> anyhow::Result<SweepLog> {
+        let statedir = xdg_dir("XDG_STATE_HOME", ".local/state")?;
+        Ok(SweepLog::new(statedir.join("forall").join("sweeps.jsonl")))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn append(&self, record: &SweepRecord) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        let mut fp = fs_err::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(record).expect("JSONification should not fail");
+        writeln!(fp, "{line}")?;
+        Ok(())
+    }
+
+    /// Read all records from the log in the order in which they were added.
+    /// If the log does not exist, an empty list is returned.
+    pub(crate) fn read(&self) -> anyhow::Result<Vec<SweepRecord>> {
+        let fp = match fs_err::File::open(&self.path) {
+            Ok(fp) => BufReader::new(fp),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut records = Vec::new();
+        for (i, line) in fp.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            records.push(serde_json::from_str(&line).with_context(|| {
+                format!(
+                    "failed to parse line {} of {}",
+                    i.saturating_add(1),
+                    self.path.display()
+                )
+            })?);
+        }
+        Ok(records)
+    }
+
+    /// Read the records for the given sweep branch.  If `branch` is `None`,
+    /// the records for the branch of the most recently-added record are
+    /// returned instead.
+    pub(crate) fn read_sweep(&self, branch: Option<&str>) -> anyhow::Result<Vec<SweepRecord>> {
+        let mut records = self.read()?;
+        let Some(branch) = branch
+            .map(ToOwned::to_owned)
+            .or_else(|| records.last().map(|r| r.branch.clone()))
+        else {
+            return Ok(Vec::new());
+        };
+        records.retain(|r| r.branch == branch);
+        Ok(records)
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A string containing `{name}`-style placeholders that are replaced with
+/// values when rendering.  Literal braces are written as `{{` and `}}`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Template(Vec<Segment>);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+    Literal(String),
+    Placeholder(String),
+}
+
+impl Template {
+    /// Return a template without any placeholders that renders to `s`
+    pub(crate) fn literal(s: &str) -> Template {
+        if s.is_empty() {
+            Template(Vec::new())
+        } else {
+            Template(vec![Segment::Literal(s.to_owned())])
+        }
+    }
+
+    /// Return an error if the template contains any placeholders not in
+    /// `known`
+    pub(crate) fn check_placeholders(&self, known: &[&str]) -> Result<(), TemplateError> {
+        for seg in &self.0 {
+            if let Segment::Placeholder(name) = seg
+                && !known.contains(&name.as_str())
+            {
+                return Err(TemplateError::Unknown(name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn uses(&self, placeholder: &str) -> bool {
+        self.0
+            .iter()
+            .any(|seg| matches!(seg, Segment::Placeholder(name) if name == placeholder))
+    }
+
+    /// Render the template, replacing each placeholder with its value in
+    /// `vars`.  Placeholders without values are replaced with the empty
+    /// string.
+    pub(crate) fn render(&self, vars: &HashMap<&str, String>) -> String {
+        let mut s = String::new();
+        for seg in &self.0 {
+            match seg {
+                Segment::Literal(lit) => s.push_str(lit),
+                Segment::Placeholder(name) => {
+                    if let Some(value) = vars.get(name.as_str()) {
+                        s.push_str(value);
+                    }
+                }
+            }
+        }
+        s
+    }
+}
+
+impl std::str::FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Template, TemplateError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '{' => {
+                    let Some((name, rest)) = chars.as_str().split_once('}') else {
+                        return Err(TemplateError::Unclosed);
+                    };
+                    let name = name.trim();
+                    if name.is_empty() || name.contains('{') {
+                        return Err(TemplateError::InvalidPlaceholder(name.to_owned()));
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Placeholder(name.to_owned()));
+                    chars = rest.chars();
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(TemplateError::Unmatched),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Template(segments))
+    }
+}
+
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub(crate) enum TemplateError {
+    #[error("unclosed '{{' in template; use '{{{{' for a literal brace")]
+    Unclosed,
+    #[error("unmatched '}}' in template; use '}}}}' for a literal brace")]
+    Unmatched,
+    #[error("invalid placeholder {{{0}}} in template")]
+    InvalidPlaceholder(String),
+    #[error("unknown placeholder {{{0}}} in template")]
+    Unknown(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Plain text", "Plain text")]
+    #[case("", "")]
+    #[case("Update {name}", "Update foo")]
+    #[case("{name} ({ghrepo})", "foo (octocat/foo)")]
+    #[case("{ name }", "foo")]
+    #[case("{{name}} is {name}", "{name} is foo")]
+    #[case("fn main() {{ }}", "fn main() { }")]
+    #[case("Missing: {branch}.", "Missing: .")]
+    fn test_render(#[case] template: &str, #[case] rendered: &str) {
+        let vars = HashMap::from([
+            ("name", String::from("foo")),
+            ("ghrepo", String::from("octocat/foo")),
+        ]);
+        let tmpl = template.parse::<Template>().unwrap();
+        assert_eq!(tmpl.render(&vars), rendered);
+    }
+
+    #[rstest]
+    #[case("Update {name", TemplateError::Unclosed)]
+    #[case("Update name}", TemplateError::Unmatched)]
+    #[case("Update {}", TemplateError::InvalidPlaceholder(String::new()))]
+    #[case("{a{b}", TemplateError::InvalidPlaceholder(String::from("a{b")))]
+    fn test_parse_error(#[case] template: &str, #[case] err: TemplateError) {
+        assert_eq!(template.parse::<Template>(), Err(err));
+    }
+
+    #[test]
+    fn test_literal() {
+        let s = "fn main() { println!(\"{name}\"); }}";
+        let tmpl = Template::literal(s);
+        assert!(!tmpl.uses("name"));
+        assert_eq!(
+            tmpl.render(&HashMap::from([("name", String::from("foo"))])),
+            s
+        );
+    }
+
+    #[test]
+    fn test_check_placeholders() {
+        let tmpl = "{name} on {branch}".parse::<Template>().unwrap();
+        assert!(tmpl.uses("name"));
+        assert!(!tmpl.uses("ghrepo"));
+        assert_eq!(tmpl.check_placeholders(&["name", "branch"]), Ok(()));
+        assert_eq!(
+            tmpl.check_placeholders(&["name"]),
+            Err(TemplateError::Unknown(String::from("branch")))
+        );
+    }
+}
@@ -1,11 +1,11 @@
-use crate::cmd::{CommandError, CommandKind, CommandPlus};
+use crate::cmd::{CapturedOutput, CommandError, CommandKind, CommandLine, CommandPlus};
 use crate::logging::Verbosity;
 use crate::project::Project;
+use anyhow::Context;
 use clap::{ArgAction, Args};
-use ghrepo::GHRepo;
 use std::ffi::OsString;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Args, Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -64,6 +64,48 @@ impl Runner {
             .kind(CommandKind::Run)
             .run()
     }
+
+    /// Return the command line that would be run for the given project
+    pub(crate) fn cmdline(&self, p: &Project) -> CommandLine {
+        p.runcmd(&self.command)
+            .args(self.args.iter())
+            .cmdline()
+            .clone()
+    }
+
+    /// Run the command on the given project, writing its output to `log`
+    pub(crate) fn run_logged<W: std::io::Write>(
+        &self,
+        p: &Project,
+        log: &mut W,
+    ) -> Result<(), CommandError> {
+        p.runcmd(&self.command)
+            .args(self.args.iter())
+            .kind(CommandKind::Run)
+            .run_logged(log)
+    }
+
+    /// Run the command on the given project and return its stdout.  The
+    /// command's stderr is captured as well: it is discarded if the command
+    /// succeeds and is included in the returned error if it fails.
+    ///
+    /// `query` relies on this so that nothing from the command is printed
+    /// while the outputs are being collected and so that a failing command's
+    /// stdout is available from the error for grouping.
+    pub(crate) fn check_output(&self, p: &Project) -> Result<String, CommandError> {
+        p.runcmd(&self.command)
+            .args(self.args.iter())
+            .kind(CommandKind::Filter)
+            .check_output()
+    }
+
+    /// Run the command on the given project, capturing its output
+    pub(crate) fn run_capture(&self, p: &Project) -> Result<CapturedOutput, CommandError> {
+        p.runcmd(&self.command)
+            .args(self.args.iter())
+            .kind(CommandKind::Run)
+            .run_capture()
+    }
 }
 
 impl TryFrom<RunOpts> for Runner {
@@ -133,7 +175,8 @@ pub(crate) enum RunOptsError {
     Canonicalize(#[source] std::io::Error),
 }
 
-pub(crate) fn get_ghrepo(p: &Path) -> anyhow::Result<Option<GHRepo>> {
+/// Return the URL of the `origin` remote of the Git repository at `p`, if any
+pub(crate) fn get_origin_url(p: &Path) -> anyhow::Result<Option<String>> {
     // Don't use ghrepo::LocalRepo::github_remote(), as that doesn't suppress
     // stderr from Git.
     match CommandPlus::new("git")
@@ -142,12 +185,58 @@ pub(crate) fn get_ghrepo(p: &Path) -> anyhow::Result<Option<GHRepo>> {
         .kind(CommandKind::Filter)
         .check_output()
     {
-        Ok(s) => Ok(GHRepo::from_url(s.trim()).ok()),
+        Ok(s) => Ok(Some(s.trim().to_owned())),
         Err(CommandError::Exit { rc, .. }) if rc.code() == Some(2) => Ok(None),
         Err(e) => Err(e.into()),
     }
 }
 
+/// Test whether `s` matches the shell-style glob pattern `pattern`, in which
+/// `*` matches any sequence of characters and `?` matches any single
+/// character
+pub(crate) fn glob_match(pattern: &str, s: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let s = s.chars().collect::<Vec<_>>();
+    let (mut pi, mut si) = (0, 0);
+    // Position in `pattern` just after the most recent `*` and the position
+    // in `s` that it is currently matched up to
+    let mut backtrack = None;
+    while si < s.len() {
+        match pattern.get(pi) {
+            Some('*') => {
+                pi += 1;
+                backtrack = Some((pi, si));
+            }
+            Some(&c) if c == '?' || c == s[si] => {
+                pi += 1;
+                si += 1;
+            }
+            _ => {
+                let Some((bp, bs)) = backtrack else {
+                    return false;
+                };
+                pi = bp;
+                si = bs + 1;
+                backtrack = Some((bp, si));
+            }
+        }
+    }
+    pattern[pi..].iter().all(|&c| c == '*')
+}
+
+/// Return the directory given by the XDG base directory environment variable
+/// `envvar`, falling back to `default` (a path relative to the user's home
+/// directory) if it is not set
+pub(crate) fn xdg_dir(envvar: &str, default: &str) -> anyhow::Result<PathBuf> {
+    match std::env::var_os(envvar).filter(|s| !s.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .context("could not determine home directory")?
+            .join(default)),
+    }
+}
+
 pub(crate) fn get_shell() -> OsString {
     std::env::var_os("SHELL").unwrap_or_else(|| OsString::from("sh"))
 }
@@ -187,4 +276,23 @@ mod tests {
         };
         assert_eq!(opts.verbosity(), verbosity);
     }
+
+    #[rstest]
+    #[case("forall-runpr-*", "forall-runpr-20250101000000", true)]
+    #[case("forall-runpr-*", "forall-runpr-", true)]
+    #[case("forall-runpr-*", "main", false)]
+    #[case("*", "", true)]
+    #[case("", "", true)]
+    #[case("", "a", false)]
+    #[case("a?c", "abc", true)]
+    #[case("a?c", "ac", false)]
+    #[case("*-bump-*", "deps-bump-serde", true)]
+    #[case("*-bump-*", "deps-bump", false)]
+    #[case("a*b*c", "aXbYbZc", true)]
+    #[case("a*b*c", "aXbYbZ", false)]
+    #[case("exact", "exact", true)]
+    #[case("exact", "exactly", false)]
+    fn test_glob_match(#[case] pattern: &str, #[case] s: &str, #[case] m: bool) {
+        assert_eq!(glob_match(pattern, s), m);
+    }
 }